@@ -0,0 +1,247 @@
+use embedded_graphics::{
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+use epd_waveshare::color::TriColor;
+use u8g2_fonts::{
+    fonts::{u8g2_font_helvB14_tf, u8g2_font_helvR08_tf},
+    types::{FontColor, HorizontalAlignment, VerticalPosition},
+    FontRenderer,
+};
+
+use crate::{
+    constants::{DISPLAY_HEIGHT, DISPLAY_WIDTH},
+    locale::Language,
+};
+
+/// typical discharge curve of a single LiPo cell as (voltage, percent)
+const DISCHARGE_CURVE: [(f32, u8); 21] = [
+    (4.20, 100),
+    (4.15, 95),
+    (4.11, 90),
+    (4.08, 85),
+    (4.02, 80),
+    (3.98, 75),
+    (3.95, 70),
+    (3.91, 65),
+    (3.87, 60),
+    (3.85, 55),
+    (3.84, 50),
+    (3.82, 45),
+    (3.80, 40),
+    (3.79, 35),
+    (3.77, 30),
+    (3.75, 25),
+    (3.73, 20),
+    (3.71, 15),
+    (3.69, 10),
+    (3.61, 5),
+    (3.27, 0),
+];
+
+/// below this level the gauge is drawn red
+const LOW_PERCENT: u8 = 20;
+
+/// battery voltage measured with the ADC. Selected with the `BATTERY_PIN`, `BATTERY_DIVIDER` and `BATTERY_CRITICAL` environment variables.
+#[derive(Debug, Clone)]
+pub struct BatteryConfig {
+    /// gpio connected to the voltage divider
+    pub pin: i32,
+    /// battery voltage divided by the voltage at the pin
+    pub divider: f32,
+    /// below this percentage only the charge screen is shown and the device sleeps until it is reset
+    pub critical: u8,
+}
+
+impl BatteryConfig {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            pin: option_env!("BATTERY_PIN")?.parse().ok()?,
+            divider: option_env!("BATTERY_DIVIDER")
+                .and_then(|divider| divider.parse().ok())
+                .unwrap_or(2.0),
+            critical: option_env!("BATTERY_CRITICAL")
+                .and_then(|critical| critical.parse().ok())
+                .unwrap_or(5),
+        })
+    }
+
+    pub fn is_critical(&self, level: &BatteryLevel) -> bool {
+        level.percent() < self.critical
+    }
+
+    /// average some samples of the pin and scale them with the divider
+    #[cfg(target_os = "espidf")]
+    pub fn read(&self) -> anyhow::Result<BatteryLevel> {
+        use esp_idf_svc::sys::*;
+
+        const SAMPLES: i32 = 8;
+
+        let mut unit: adc_unit_t = Default::default();
+        let mut channel: adc_channel_t = Default::default();
+        esp!(unsafe { adc_oneshot_io_to_channel(self.pin, &mut unit, &mut channel) })?;
+
+        let mut handle: adc_oneshot_unit_handle_t = core::ptr::null_mut();
+        let unit_config = adc_oneshot_unit_init_cfg_t {
+            unit_id: unit,
+            ..Default::default()
+        };
+        esp!(unsafe { adc_oneshot_new_unit(&unit_config, &mut handle) })?;
+
+        let channel_config = adc_oneshot_chan_cfg_t {
+            atten: adc_atten_t_ADC_ATTEN_DB_12,
+            bitwidth: adc_bitwidth_t_ADC_BITWIDTH_DEFAULT,
+        };
+        let mut calibration: adc_cali_handle_t = core::ptr::null_mut();
+        let calibration_config = adc_cali_curve_fitting_config_t {
+            unit_id: unit,
+            chan: channel,
+            atten: adc_atten_t_ADC_ATTEN_DB_12,
+            bitwidth: adc_bitwidth_t_ADC_BITWIDTH_DEFAULT,
+        };
+
+        let millivolts = (|| {
+            esp!(unsafe { adc_oneshot_config_channel(handle, channel, &channel_config) })?;
+            esp!(unsafe {
+                adc_cali_create_scheme_curve_fitting(&calibration_config, &mut calibration)
+            })?;
+            let mut sum = 0;
+            for _ in 0..SAMPLES {
+                let mut raw = 0;
+                let mut millivolts = 0;
+                esp!(unsafe { adc_oneshot_read(handle, channel, &mut raw) })?;
+                esp!(unsafe { adc_cali_raw_to_voltage(calibration, raw, &mut millivolts) })?;
+                sum += millivolts;
+            }
+            Ok::<_, EspError>(sum / SAMPLES)
+        })();
+
+        unsafe {
+            if !calibration.is_null() {
+                adc_cali_delete_scheme_curve_fitting(calibration);
+            }
+            adc_oneshot_del_unit(handle);
+        }
+
+        let level = BatteryLevel {
+            voltage: millivolts? as f32 / 1000.0 * self.divider,
+        };
+        log::info!("battery at {:.2} V ({} %)", level.voltage, level.percent());
+        Ok(level)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BatteryLevel {
+    pub voltage: f32,
+}
+
+impl BatteryLevel {
+    /// interpolated state of charge from the discharge curve
+    pub fn percent(&self) -> u8 {
+        let (full, _) = DISCHARGE_CURVE[0];
+        let (empty, _) = DISCHARGE_CURVE[DISCHARGE_CURVE.len() - 1];
+        if self.voltage >= full {
+            return 100;
+        }
+        if self.voltage <= empty {
+            return 0;
+        }
+        DISCHARGE_CURVE
+            .windows(2)
+            .find(|points| self.voltage >= points[1].0)
+            .map(|points| {
+                let (high_voltage, high_percent) = points[0];
+                let (low_voltage, low_percent) = points[1];
+                let ratio = (self.voltage - low_voltage) / (high_voltage - low_voltage);
+                low_percent + (ratio * (high_percent - low_percent) as f32).round() as u8
+            })
+            .unwrap_or(0)
+    }
+
+    /// small battery symbol with the percentage left of it. `point` is the top right corner,
+    /// a low level is drawn in `highlight`.
+    pub fn draw_gauge<Display>(&self, display: &mut Display, point: Point, highlight: TriColor)
+    where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        const WIDTH: u32 = 28;
+        const HEIGHT: u32 = 14;
+
+        let percent = self.percent();
+        let color = if percent < LOW_PERCENT {
+            highlight
+        } else {
+            TriColor::Black
+        };
+
+        let body = Rectangle::new(
+            point - Point::new(WIDTH as i32 + 3, 0),
+            Size::new(WIDTH, HEIGHT),
+        );
+        body.into_styled(PrimitiveStyle::with_stroke(TriColor::Black, 1))
+            .draw(display)
+            .unwrap();
+        Rectangle::new(
+            body.top_left + Point::new(WIDTH as i32, 4),
+            Size::new(3, HEIGHT - 8),
+        )
+        .into_styled(PrimitiveStyle::with_fill(TriColor::Black))
+        .draw(display)
+        .unwrap();
+        let fill = (WIDTH - 4) * percent as u32 / 100;
+        if fill > 0 {
+            Rectangle::new(
+                body.top_left + Point::new(2, 2),
+                Size::new(fill, HEIGHT - 4),
+            )
+            .into_styled(PrimitiveStyle::with_fill(color))
+            .draw(display)
+            .unwrap();
+        }
+
+        FontRenderer::new::<u8g2_font_helvR08_tf>()
+            .render_aligned(
+                format!("{} %", percent).as_str(),
+                body.top_left + Point::new(-4, HEIGHT as i32 / 2),
+                VerticalPosition::Center,
+                HorizontalAlignment::Right,
+                FontColor::Transparent(color),
+                display,
+            )
+            .unwrap();
+    }
+}
+
+/// full screen asking to charge the battery, shown before sleeping until the device is reset
+pub fn draw_charge_screen<Display>(display: &mut Display, level: &BatteryLevel, language: Language)
+where
+    Display: DrawTarget<Color = TriColor>,
+    Display::Error: std::fmt::Debug,
+{
+    display.clear(TriColor::White).unwrap();
+    let center = Point::new(DISPLAY_WIDTH as i32 / 2, DISPLAY_HEIGHT as i32 / 2);
+    FontRenderer::new::<u8g2_font_helvB14_tf>()
+        .render_aligned(
+            language.charge_battery(),
+            center,
+            VerticalPosition::Baseline,
+            HorizontalAlignment::Center,
+            FontColor::Transparent(TriColor::Chromatic),
+            display,
+        )
+        .unwrap();
+    FontRenderer::new::<u8g2_font_helvR08_tf>()
+        .render_aligned(
+            language
+                .localize_number(&format!("{:.2} V", level.voltage))
+                .as_str(),
+            center + Point::new(0, 25),
+            VerticalPosition::Baseline,
+            HorizontalAlignment::Center,
+            FontColor::Transparent(TriColor::Black),
+            display,
+        )
+        .unwrap();
+}
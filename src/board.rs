@@ -0,0 +1,185 @@
+/// wiring of the panel and the spi settings. Selected with the `BOARD` environment variable: the name of a preset,
+/// optionally followed by single settings like `driver-board,busy=4,frequency=2000000`. Without it the `hat` preset is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    /// spi peripheral, 2 is available on every chip, 3 only on the esp32, esp32-s2 and esp32-s3
+    pub spi_host: u8,
+    /// spi clock in Hz
+    pub frequency: u32,
+    pub sclk: i32,
+    pub mosi: i32,
+    pub cs: i32,
+    pub busy: i32,
+    pub reset: i32,
+    pub dc: i32,
+    /// gpio switching the supply of the panel, if the board has one
+    pub power: Option<i32>,
+    /// whether the panel is powered with the power pin high
+    pub power_active_high: bool,
+    /// push button to gnd which wakes the device, it has to be an rtc gpio
+    pub button: Option<i32>,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    #[error("unknown board setting {0}")]
+    UnknownSetting(String),
+    #[error("invalid value for the board setting {0}")]
+    InvalidValue(String),
+    #[error("gpio {0} does not exist")]
+    InvalidPin(i32),
+    #[error("gpio {0} is used twice")]
+    DuplicatePin(i32),
+    #[error("gpio {0} can not wake the device, the button needs an rtc gpio")]
+    NoWakePin(i32),
+    #[error("spi host {0} is not available on this chip, use 2 or 3")]
+    InvalidHost(u8),
+    #[error("spi frequency {0} Hz is outside of 100 kHz to 20 MHz")]
+    InvalidFrequency(u32),
+}
+
+/// what the chip offers for the wiring, checked by [`Board::validate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chip {
+    pub gpio_count: i32,
+    /// only the esp32, esp32-s2 and esp32-s3 have the spi host 3
+    pub spi3: bool,
+    /// bit mask of the rtc gpios, only they wake the device from the deep sleep. On the esp32-c6 the lp gpios 0 to 7.
+    pub rtc_pins: u64,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::hat()
+    }
+}
+
+impl Board {
+    /// the Waveshare e-paper hat wired to the esp32-c6
+    pub fn hat() -> Self {
+        Self {
+            spi_host: 2,
+            frequency: 5_000_000,
+            sclk: 21,
+            mosi: 19,
+            cs: 18,
+            busy: 1,
+            reset: 2,
+            dc: 3,
+            power: Some(0),
+            power_active_high: true,
+            button: None,
+        }
+    }
+
+    /// the Waveshare e-paper esp32 driver board
+    pub fn driver_board() -> Self {
+        Self {
+            spi_host: 2,
+            frequency: 4_000_000,
+            sclk: 13,
+            mosi: 14,
+            cs: 15,
+            busy: 25,
+            reset: 26,
+            dc: 27,
+            power: None,
+            power_active_high: true,
+            button: None,
+        }
+    }
+
+    /// parse the `BOARD` code, later settings overwrite earlier ones.
+    /// The pins are checked later with `validate`, as the number of gpios depends on the chip.
+    pub fn from_code(code: &str) -> Result<Self, BoardError> {
+        let mut board = Self::default();
+        for setting in code.split(',').map(str::trim) {
+            match setting {
+                "" => continue,
+                "hat" => {
+                    board = Self::hat();
+                    continue;
+                }
+                "driver-board" => {
+                    board = Self::driver_board();
+                    continue;
+                }
+                _ => {}
+            }
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or_else(|| BoardError::UnknownSetting(setting.to_string()))?;
+            let invalid = || BoardError::InvalidValue(key.to_string());
+            let pin = || value.parse::<i32>().map_err(|_| invalid());
+            match key {
+                "host" => board.spi_host = value.parse().map_err(|_| invalid())?,
+                "frequency" => board.frequency = value.parse().map_err(|_| invalid())?,
+                "sclk" => board.sclk = pin()?,
+                "mosi" => board.mosi = pin()?,
+                "cs" => board.cs = pin()?,
+                "busy" => board.busy = pin()?,
+                "rst" => board.reset = pin()?,
+                "dc" => board.dc = pin()?,
+                "power" => {
+                    board.power = match value {
+                        "none" => None,
+                        value => Some(value.parse().map_err(|_| invalid())?),
+                    }
+                }
+                "button" => {
+                    board.button = match value {
+                        "none" => None,
+                        value => Some(value.parse().map_err(|_| invalid())?),
+                    }
+                }
+                "power_active" => {
+                    board.power_active_high = match value {
+                        "high" => true,
+                        "low" => false,
+                        _ => return Err(invalid()),
+                    }
+                }
+                _ => return Err(BoardError::UnknownSetting(key.to_string())),
+            }
+        }
+        Ok(board)
+    }
+
+    pub fn from_env() -> Result<Self, BoardError> {
+        option_env!("BOARD").map_or_else(|| Ok(Self::default()), Self::from_code)
+    }
+
+    /// check the settings against the gpios and spi hosts of the `chip`
+    pub fn validate(&self, chip: &Chip) -> Result<(), BoardError> {
+        if !(self.spi_host == 2 || (self.spi_host == 3 && chip.spi3)) {
+            return Err(BoardError::InvalidHost(self.spi_host));
+        }
+        if !(100_000..=20_000_000).contains(&self.frequency) {
+            return Err(BoardError::InvalidFrequency(self.frequency));
+        }
+        let pins = self.pins();
+        for (i, pin) in pins.iter().enumerate() {
+            if !(0..chip.gpio_count).contains(pin) {
+                return Err(BoardError::InvalidPin(*pin));
+            }
+            if pins[..i].contains(pin) {
+                return Err(BoardError::DuplicatePin(*pin));
+            }
+        }
+        if let Some(button) = self.button.filter(|pin| chip.rtc_pins & (1 << pin) == 0) {
+            return Err(BoardError::NoWakePin(button));
+        }
+        Ok(())
+    }
+
+    /// every gpio used by the panel and the button
+    pub fn pins(&self) -> Vec<i32> {
+        [
+            self.sclk, self.mosi, self.cs, self.busy, self.reset, self.dc,
+        ]
+        .into_iter()
+        .chain(self.power)
+        .chain(self.button)
+        .collect()
+    }
+}
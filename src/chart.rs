@@ -0,0 +1,371 @@
+use embedded_graphics::{
+    prelude::*,
+    primitives::{Line, PrimitiveStyle, Rectangle},
+};
+use epd_waveshare::color::TriColor;
+use u8g2_fonts::{
+    fonts::u8g2_font_helvR08_tf,
+    types::{FontColor, HorizontalAlignment, VerticalPosition},
+    FontRenderer,
+};
+
+/// maps the samples of a series into `area`. Sample `i` is drawn `i * step` pixels right of the left edge,
+/// `min` on the bottom row and `max` on the top row. Values outside of the range are not clipped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Chart {
+    pub area: Rectangle,
+    /// pixels between two samples
+    pub step: u32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Chart {
+    pub fn x(&self, index: usize) -> i32 {
+        self.area.top_left.x + (index as u32 * self.step) as i32
+    }
+
+    pub fn y(&self, value: f32) -> i32 {
+        let range = self.area.size.height.saturating_sub(1) as f32;
+        self.bottom() - ((value - self.min) / (self.max - self.min) * range).round() as i32
+    }
+
+    pub fn point(&self, index: usize, value: f32) -> Point {
+        Point::new(self.x(index), self.y(value))
+    }
+
+    /// the lowest row of the area
+    pub fn bottom(&self) -> i32 {
+        self.area.top_left.y + self.area.size.height as i32 - 1
+    }
+
+    /// number of samples which fit into the width of the area, the last one may be on the right edge
+    pub fn samples(&self) -> usize {
+        match self.area.size.width {
+            0 => 0,
+            width => ((width - 1) / self.step.max(1)) as usize + 1,
+        }
+    }
+}
+
+/// index of the highest sample of every run of samples above `min`, to label each group of bars once
+pub fn peaks(values: impl IntoIterator<Item = f32>, min: f32) -> Vec<usize> {
+    let mut peaks = Vec::new();
+    let mut run: Option<(usize, f32)> = None;
+    for (i, value) in values.into_iter().enumerate() {
+        if value <= min {
+            peaks.extend(run.take().map(|(peak, _)| peak));
+        } else if run.is_none_or(|(_, highest)| value > highest) {
+            run = Some((i, value));
+        }
+    }
+    peaks.extend(run.map(|(peak, _)| peak));
+    peaks
+}
+
+/// pattern of a dashed line in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dash {
+    pub on: u32,
+    pub off: u32,
+}
+
+impl Dash {
+    /// whether the `n`th pixel along the line is drawn
+    pub fn is_on(&self, n: u32) -> bool {
+        n % (self.on + self.off).max(1) < self.on
+    }
+}
+
+/// draw a one pixel wide dashed line, `n` counts the pixels drawn so far so the pattern continues over several segments
+pub fn draw_dashed<Display>(
+    display: &mut Display,
+    line: Line,
+    color: TriColor,
+    dash: Dash,
+    n: &mut u32,
+) where
+    Display: DrawTarget<Color = TriColor>,
+    Display::Error: std::fmt::Debug,
+{
+    let pixels = line.points().filter_map(|point| {
+        let on = dash.is_on(*n);
+        *n += 1;
+        on.then_some(Pixel(point, color))
+    });
+    display.draw_iter(pixels).unwrap();
+}
+
+/// connects the samples with straight lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineSeries {
+    pub color: TriColor,
+    pub width: u32,
+    /// dashed lines are always one pixel wide
+    pub dash: Option<Dash>,
+}
+
+impl LineSeries {
+    pub fn new(color: TriColor, width: u32) -> Self {
+        Self {
+            color,
+            width,
+            dash: None,
+        }
+    }
+
+    pub fn dashed(color: TriColor, dash: Dash) -> Self {
+        Self {
+            color,
+            width: 1,
+            dash: Some(dash),
+        }
+    }
+
+    pub fn draw<Display>(
+        &self,
+        display: &mut Display,
+        chart: &Chart,
+        values: impl IntoIterator<Item = f32>,
+    ) where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        let mut last = None;
+        let mut n = 0;
+        for (i, value) in values.into_iter().take(chart.samples()).enumerate() {
+            let point = chart.point(i, value);
+            match (last, self.dash) {
+                (None, _) => {}
+                (Some(last), None) => Line::new(last, point)
+                    .into_styled(PrimitiveStyle::with_stroke(self.color, self.width))
+                    .draw(display)
+                    .unwrap(),
+                (Some(last), Some(dash)) => {
+                    draw_dashed(display, Line::new(last, point), self.color, dash, &mut n);
+                    // the shared end point is drawn by the next segment again
+                    n -= 1;
+                }
+            }
+            last = Some(point);
+        }
+    }
+}
+
+/// bars from the bottom of the chart centered on their sample, samples at or below `min` are left out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarSeries {
+    pub color: TriColor,
+    pub width: u32,
+}
+
+impl BarSeries {
+    pub fn draw<Display>(
+        &self,
+        display: &mut Display,
+        chart: &Chart,
+        values: impl IntoIterator<Item = f32>,
+    ) where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        for (i, value) in values.into_iter().take(chart.samples()).enumerate() {
+            if value <= chart.min {
+                continue;
+            }
+            let top = chart.y(value).min(chart.bottom());
+            Rectangle::new(
+                Point::new(chart.x(i) - self.width as i32 / 2, top),
+                Size::new(self.width, (chart.bottom() - top + 1) as u32),
+            )
+            .into_styled(PrimitiveStyle::with_fill(self.color))
+            .draw(display)
+            .unwrap();
+        }
+    }
+}
+
+/// fills the area between the samples and the bottom of the chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AreaSeries {
+    pub color: TriColor,
+}
+
+impl AreaSeries {
+    pub fn draw<Display>(
+        &self,
+        display: &mut Display,
+        chart: &Chart,
+        values: impl IntoIterator<Item = f32>,
+    ) where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        let style = PrimitiveStyle::with_stroke(self.color, 1);
+        let mut last: Option<Point> = None;
+        for (i, value) in values.into_iter().take(chart.samples()).enumerate() {
+            let point = chart.point(i, value);
+            let Some(start) = last.replace(point) else {
+                continue;
+            };
+            // one column per pixel, the last column is filled by the next segment or below
+            let width = (point.x - start.x).max(1);
+            for column in 0..width {
+                let y = start.y + (point.y - start.y) * column / width;
+                Line::new(
+                    Point::new(start.x + column, y),
+                    Point::new(start.x + column, chart.bottom()),
+                )
+                .into_styled(style)
+                .draw(display)
+                .unwrap();
+            }
+        }
+        if let Some(last) = last {
+            Line::new(last, Point::new(last.x, chart.bottom()))
+                .into_styled(style)
+                .draw(display)
+                .unwrap();
+        }
+    }
+}
+
+/// line along the bottom of the chart with ticks and labels below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeAxis {
+    pub color: TriColor,
+    /// length of the ticks below the line
+    pub tick: u32,
+}
+
+impl TimeAxis {
+    /// `labels` are the sample indices with their text
+    pub fn draw<Display, Label>(
+        &self,
+        display: &mut Display,
+        chart: &Chart,
+        labels: impl IntoIterator<Item = (usize, Label)>,
+    ) where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+        Label: AsRef<str>,
+    {
+        let style = PrimitiveStyle::with_stroke(self.color, 1);
+        let y = chart.bottom() + 1;
+        let end = chart.area.top_left.x + chart.area.size.width as i32 - 1;
+        Line::new(Point::new(chart.area.top_left.x, y), Point::new(end, y))
+            .into_styled(style)
+            .draw(display)
+            .unwrap();
+        for (i, label) in labels {
+            let x = chart.x(i);
+            Line::new(Point::new(x, y), Point::new(x, y + self.tick as i32))
+                .into_styled(style)
+                .draw(display)
+                .unwrap();
+            FontRenderer::new::<u8g2_font_helvR08_tf>()
+                .render_aligned(
+                    label.as_ref(),
+                    Point::new(x, y + self.tick as i32 + 1),
+                    VerticalPosition::Top,
+                    HorizontalAlignment::Center,
+                    FontColor::Transparent(self.color),
+                    display,
+                )
+                .unwrap();
+        }
+    }
+}
+
+/// line along the left edge of the chart with ticks and labels left of it and optional gridlines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueAxis {
+    pub color: TriColor,
+    pub tick: u32,
+    /// dashed gridlines across the chart at every tick
+    pub grid: Option<Dash>,
+}
+
+impl ValueAxis {
+    /// `labels` are the values with their text
+    pub fn draw<Display, Label>(
+        &self,
+        display: &mut Display,
+        chart: &Chart,
+        labels: impl IntoIterator<Item = (f32, Label)>,
+    ) where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+        Label: AsRef<str>,
+    {
+        let style = PrimitiveStyle::with_stroke(self.color, 1);
+        let x = chart.area.top_left.x - 1;
+        Line::new(
+            Point::new(x, chart.area.top_left.y),
+            Point::new(x, chart.bottom()),
+        )
+        .into_styled(style)
+        .draw(display)
+        .unwrap();
+        for (value, label) in labels {
+            let y = chart.y(value);
+            if let Some(dash) = self.grid {
+                let end = chart.area.top_left.x + chart.area.size.width as i32 - 1;
+                let line = Line::new(Point::new(x + 1, y), Point::new(end, y));
+                draw_dashed(display, line, self.color, dash, &mut 0);
+            }
+            Line::new(Point::new(x - self.tick as i32, y), Point::new(x, y))
+                .into_styled(style)
+                .draw(display)
+                .unwrap();
+            FontRenderer::new::<u8g2_font_helvR08_tf>()
+                .render_aligned(
+                    label.as_ref(),
+                    Point::new(x - self.tick as i32 - 2, y),
+                    VerticalPosition::Center,
+                    HorizontalAlignment::Right,
+                    FontColor::Transparent(self.color),
+                    display,
+                )
+                .unwrap();
+        }
+    }
+}
+
+/// a row of colored squares with the name of their series
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Legend<'a> {
+    pub entries: &'a [(&'a str, TriColor)],
+}
+
+impl Legend<'_> {
+    const SWATCH: u32 = 8;
+
+    /// `point` is the top left corner of the first entry
+    pub fn draw<Display>(&self, display: &mut Display, point: Point)
+    where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        let font = FontRenderer::new::<u8g2_font_helvR08_tf>();
+        let mut x = point.x;
+        for (name, color) in self.entries {
+            Rectangle::new(Point::new(x, point.y), Size::new_equal(Self::SWATCH))
+                .into_styled(PrimitiveStyle::with_fill(*color))
+                .draw(display)
+                .unwrap();
+            x += Self::SWATCH as i32 + 3;
+            let text = font
+                .render_aligned(
+                    *name,
+                    Point::new(x, point.y + Self::SWATCH as i32 / 2),
+                    VerticalPosition::Center,
+                    HorizontalAlignment::Left,
+                    FontColor::Transparent(TriColor::Black),
+                    display,
+                )
+                .unwrap();
+            x += text.map_or(0, |text| text.size.width as i32) + 10;
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use chrono::{DateTime, Datelike, Utc};
+use embedded_graphics::prelude::*;
+use epd_waveshare::color::TriColor;
+use u8g2_fonts::{
+    fonts::u8g2_font_helvR08_tf,
+    types::{FontColor, HorizontalAlignment, VerticalPosition},
+    FontRenderer,
+};
+
+/// earlier times can only come from a clock which was never set
+const MIN_YEAR: i32 = 2024;
+
+/// where the current time came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSource {
+    Ntp,
+    /// the system time kept by the rtc during the deep sleep, it drifts without ntp
+    Rtc,
+    /// the `Date` header of the weather service, accurate to about a second
+    Http,
+}
+
+impl TimeSource {
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            Self::Ntp => None,
+            Self::Rtc => Some("RTC"),
+            Self::Http => Some("HTTP"),
+        }
+    }
+
+    /// mark the time if it is not synced by ntp in `color`, `point` is the center of the text
+    pub fn draw<Display>(&self, display: &mut Display, point: Point, color: TriColor)
+    where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        let Some(label) = self.label() else {
+            return;
+        };
+        FontRenderer::new::<u8g2_font_helvR08_tf>()
+            .render_aligned(
+                format!("({label})").as_str(),
+                point,
+                VerticalPosition::Baseline,
+                HorizontalAlignment::Center,
+                FontColor::Transparent(color),
+                display,
+            )
+            .unwrap();
+    }
+}
+
+/// whether the clock was set at some point. The rtc keeps the time during the deep sleep, but starts at 1970 after a reset.
+pub fn is_plausible(time: DateTime<Utc>) -> bool {
+    time.year() >= MIN_YEAR
+}
+
+/// parse a http `Date` header like `Sun, 06 Nov 1994 08:49:37 GMT`
+pub fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// set the system time, it is kept by the rtc during the deep sleep
+#[cfg(target_os = "espidf")]
+pub fn set_system_time(time: DateTime<Utc>) -> anyhow::Result<()> {
+    let value = esp_idf_svc::sys::timeval {
+        tv_sec: time.timestamp() as _,
+        tv_usec: time.timestamp_subsec_micros() as _,
+    };
+    if unsafe { esp_idf_svc::sys::settimeofday(&value, core::ptr::null()) } != 0 {
+        anyhow::bail!("failed to set the system time");
+    }
+    log::info!("system time set to {time}");
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use chrono::TimeDelta;
+
+use crate::{
+    battery::BatteryConfig, layout::Layout, locale::Language, network::KnownNetwork,
+    pages::PageSelection, panel::MonoRed, provisioning::Settings, schedule::RefreshPolicy,
+    secret::Secret, units::Units, warnings::DwdWarnings, weather::Location,
+};
+
+/// access point opened by the device
+#[derive(Debug, Clone, Default)]
+pub struct AccessPoint {
+    pub ssid: String,
+    /// WPA2 password, without it the provisioning access point uses a generated one and other access points are refused
+    pub password: Option<Secret>,
+}
+
+/// runtime configuration of the weather station. The values are taken from environment variables at compile time
+/// and can be overwritten by the settings of the provisioning form.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub language: Language,
+    pub units: Units,
+    pub refresh: RefreshPolicy,
+    /// page shown with each forecast
+    pub pages: PageSelection,
+    /// placement of the widgets
+    pub layout: Layout,
+    pub battery: Option<BatteryConfig>,
+    /// red on black and white panels
+    pub mono_red: MonoRed,
+    pub location: Location,
+    /// official weather warnings of the region, only fetched if configured
+    pub warnings: Option<DwdWarnings>,
+    pub networks: Vec<KnownNetwork>,
+    /// how long to wait for the connection to each network
+    pub wifi_timeout: Duration,
+    /// reconnect with the ip address of the last wake cycle instead of waiting for dhcp
+    pub wifi_reuse_lease: bool,
+    /// ntp servers instead of `pool.ntp.org`
+    pub ntp_servers: Vec<String>,
+    /// how long to wait for the time sync before falling back to the rtc or the weather service
+    pub sntp_timeout: Duration,
+    /// refresh the status strip in this interval between the forecasts
+    pub status_interval: Option<TimeDelta>,
+    /// partial refreshes of the status strip before a full refresh removes the ghosting
+    pub max_partial_refreshes: u8,
+    /// how long to wait for the weather service
+    pub http_timeout: Duration,
+    /// access point opened for provisioning
+    pub provisioning: AccessPoint,
+    /// access point opened next to the client connection, only if configured
+    pub access_point: Option<AccessPoint>,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Self {
+            language: option_env!("LANGUAGE")
+                .and_then(Language::from_code)
+                .unwrap_or_default(),
+            units: option_env!("UNITS")
+                .and_then(Units::from_code)
+                .unwrap_or_default(),
+            refresh: option_env!("REFRESH")
+                .and_then(RefreshPolicy::from_code)
+                .unwrap_or_default(),
+            pages: option_env!("PAGES")
+                .and_then(PageSelection::from_code)
+                .unwrap_or_default(),
+            layout: Layout::from_env().unwrap_or_else(|err| {
+                log::error!("{err}, using the default layout");
+                Layout::default()
+            }),
+            battery: BatteryConfig::from_env(),
+            mono_red: option_env!("MONO_RED")
+                .and_then(MonoRed::from_code)
+                .unwrap_or_default(),
+            location: option_env!("LOCATION")
+                .and_then(Location::from_code)
+                .unwrap_or_default(),
+            warnings: option_env!("WARNINGS").and_then(DwdWarnings::from_code),
+            networks: (option_env!("SSID").map(|ssid| KnownNetwork {
+                ssid: ssid.to_string(),
+                password: Secret::new(option_env!("PASS").unwrap_or_default()),
+                priority: 0,
+            }))
+            .into_iter()
+            .chain(option_env!("WIFI_NETWORKS").map_or_else(Vec::new, KnownNetwork::list_from_code))
+            .collect(),
+            wifi_timeout: Duration::from_secs(
+                option_env!("WIFI_TIMEOUT")
+                    .and_then(|timeout| timeout.parse().ok())
+                    .unwrap_or(15),
+            ),
+            wifi_reuse_lease: option_env!("WIFI_REUSE_LEASE")
+                .is_some_and(|reuse| matches!(reuse, "1" | "true" | "yes")),
+            ntp_servers: option_env!("NTP_SERVERS")
+                .map(|servers| {
+                    (servers.split(','))
+                        .map(str::trim)
+                        .filter(|server| !server.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            sntp_timeout: Duration::from_secs(
+                option_env!("SNTP_TIMEOUT")
+                    .and_then(|timeout| timeout.parse().ok())
+                    .unwrap_or(10),
+            ),
+            status_interval: option_env!("STATUS_INTERVAL")
+                .and_then(|minutes| minutes.parse().ok())
+                .filter(|minutes| *minutes > 0)
+                .map(TimeDelta::minutes),
+            max_partial_refreshes: option_env!("PARTIAL_REFRESHES")
+                .and_then(|count| count.parse().ok())
+                .unwrap_or(10),
+            http_timeout: Duration::from_secs(
+                option_env!("HTTP_TIMEOUT")
+                    .and_then(|timeout| timeout.parse().ok())
+                    .unwrap_or(10),
+            ),
+            provisioning: AccessPoint {
+                ssid: option_env!("PROVISIONING_AP")
+                    .unwrap_or("esp-weather")
+                    .to_string(),
+                password: option_env!("PROVISIONING_PASS").map(Secret::new),
+            },
+            access_point: option_env!("AP_SSID").map(|ssid| AccessPoint {
+                ssid: ssid.to_string(),
+                password: option_env!("AP_PASS").map(Secret::new),
+            }),
+        }
+    }
+
+    /// use the settings saved by the provisioning form instead of the compiled in values
+    pub fn apply(&mut self, settings: Settings) {
+        self.refresh = settings.refresh_policy();
+        self.location = settings.location;
+        // the network entered on site is preferred over the compiled in ones
+        self.networks
+            .retain(|network| network.ssid != settings.ssid);
+        self.networks.insert(
+            0,
+            KnownNetwork {
+                ssid: settings.ssid,
+                password: settings.password,
+                priority: u8::MAX,
+            },
+        );
+    }
+}
@@ -1,4 +1,4 @@
-pub const DISPLAY_WIDTH: u32 = epd_waveshare::epd7in5b_v3::WIDTH;
+pub const DISPLAY_WIDTH: u32 = crate::panel::WIDTH;
 pub const SECTION_WIDTH: i32 = (DISPLAY_WIDTH as i32) / 3;
-pub const DISPLAY_HEIGHT: u32 = epd_waveshare::epd7in5b_v3::HEIGHT;
+pub const DISPLAY_HEIGHT: u32 = crate::panel::HEIGHT;
 pub const HEIGHT: i32 = 50;
@@ -0,0 +1,294 @@
+use chrono::NaiveDateTime;
+use embedded_graphics::{
+    prelude::*,
+    primitives::{Arc, Circle, Line, PrimitiveStyle, Rectangle, Triangle},
+};
+use epd_waveshare::color::TriColor;
+use u8g2_fonts::{
+    fonts::{
+        u8g2_font_helvB10_tf, u8g2_font_helvB12_tf, u8g2_font_helvB14_tf, u8g2_font_helvB18_tf,
+        u8g2_font_helvR08_tf, u8g2_font_helvR10_tf,
+    },
+    types::{FontColor, HorizontalAlignment, VerticalPosition},
+    FontRenderer,
+};
+
+use crate::{
+    constants::{DISPLAY_HEIGHT, DISPLAY_WIDTH},
+    locale::Language,
+    network::NetworkStatus,
+    provisioning::SETUP_URL,
+    qr::{QrCode, QUIET_ZONE},
+    text::{text_width, TextBox},
+};
+
+/// version of the firmware shown on the error screen
+pub const FIRMWARE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// what went wrong, decides the icon and the title of the error screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// no wifi connection
+    Network,
+    /// the clock could not be set
+    Time,
+    /// the weather service failed or did not answer
+    Service,
+    /// the answer of the weather service could not be read
+    Data,
+}
+
+impl ErrorCategory {
+    pub fn title(&self, language: Language) -> &'static str {
+        match (self, language) {
+            (Self::Network, Language::English) => "No wifi connection",
+            (Self::Network, Language::German) => "Keine WLAN-Verbindung",
+            (Self::Time, Language::English) => "Time unknown",
+            (Self::Time, Language::German) => "Uhrzeit unbekannt",
+            (Self::Service, Language::English) => "Weather service unavailable",
+            (Self::Service, Language::German) => "Wetterdienst nicht erreichbar",
+            (Self::Data, Language::English) => "Invalid forecast",
+            (Self::Data, Language::German) => "Ungültige Vorhersage",
+        }
+    }
+
+    /// draw the icon into a square of `size` pixels at `top_left`
+    pub fn draw_icon<Display>(&self, display: &mut Display, top_left: Point, size: u32)
+    where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        let black = PrimitiveStyle::with_stroke(TriColor::Black, 3);
+        let red = PrimitiveStyle::with_stroke(TriColor::Chromatic, 3);
+        let s = size as i32;
+        let center = top_left + Point::new(s / 2, s / 2);
+        match self {
+            Self::Network => {
+                // wifi arcs above a dot, crossed out
+                let base = top_left + Point::new(s / 2, s * 7 / 8);
+                for ring in 1..=3 {
+                    Arc::with_center(base, size * ring / 3, 225.0.deg(), 90.0.deg())
+                        .into_styled(black)
+                        .draw(display)
+                        .unwrap();
+                }
+                Circle::with_center(base, 6)
+                    .into_styled(PrimitiveStyle::with_fill(TriColor::Black))
+                    .draw(display)
+                    .unwrap();
+                Line::new(
+                    top_left + Point::new(s / 8, s / 8),
+                    top_left + Point::new(s * 7 / 8, s * 7 / 8),
+                )
+                .into_styled(red)
+                .draw(display)
+                .unwrap();
+            }
+            Self::Time => {
+                // clock face with a red hand
+                Circle::with_center(center, size - 4)
+                    .into_styled(black)
+                    .draw(display)
+                    .unwrap();
+                Line::new(center, center - Point::new(0, s * 3 / 8))
+                    .into_styled(black)
+                    .draw(display)
+                    .unwrap();
+                Line::new(center, center + Point::new(s / 4, 0))
+                    .into_styled(red)
+                    .draw(display)
+                    .unwrap();
+            }
+            Self::Service => {
+                // warning sign
+                Triangle::new(
+                    top_left + Point::new(s / 2, 6),
+                    top_left + Point::new(4, s - 4),
+                    top_left + Point::new(s - 5, s - 4),
+                )
+                .into_styled(red)
+                .draw(display)
+                .unwrap();
+                Line::new(center - Point::new(0, s / 6), center + Point::new(0, s / 6))
+                    .into_styled(black)
+                    .draw(display)
+                    .unwrap();
+                Circle::with_center(center + Point::new(0, s / 3), 4)
+                    .into_styled(PrimitiveStyle::with_fill(TriColor::Black))
+                    .draw(display)
+                    .unwrap();
+            }
+            Self::Data => {
+                // sheet with lines, crossed out
+                Rectangle::new(
+                    top_left + Point::new(s / 6, 2),
+                    Size::new(size * 2 / 3, size - 4),
+                )
+                .into_styled(black)
+                .draw(display)
+                .unwrap();
+                for row in 1..=3 {
+                    let y = s * row / 4;
+                    Line::new(
+                        top_left + Point::new(s / 3, y),
+                        top_left + Point::new(s * 2 / 3, y),
+                    )
+                    .into_styled(PrimitiveStyle::with_stroke(TriColor::Black, 1))
+                    .draw(display)
+                    .unwrap();
+                }
+                Line::new(
+                    top_left + Point::new(s / 8, s - 4),
+                    top_left + Point::new(s * 7 / 8, 4),
+                )
+                .into_styled(red)
+                .draw(display)
+                .unwrap();
+            }
+        }
+    }
+}
+
+/// everything shown on the error screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorReport<'a> {
+    pub category: ErrorCategory,
+    /// the error with its causes
+    pub message: &'a str,
+    /// local time of the failure
+    pub time: NaiveDateTime,
+    /// the network of the failed attempt, `None` if no connection was made
+    pub network: Option<&'a NetworkStatus>,
+    /// local time of the next attempt
+    pub retry: Option<NaiveDateTime>,
+    /// name of the provisioning access point
+    pub access_point: &'a str,
+    pub language: Language,
+}
+
+impl ErrorReport<'_> {
+    const MARGIN: i32 = 20;
+    const ICON: u32 = 48;
+    const ROW: i32 = 18;
+    /// width of the labels of the details
+    const LABEL: i32 = 130;
+
+    /// labels and values below the message
+    pub fn details(&self) -> Vec<(&'static str, String)> {
+        let language = self.language;
+        let time = |time: NaiveDateTime| {
+            format!(
+                "{} {}",
+                language.format_date(time.date()),
+                time.format("%H:%M")
+            )
+        };
+        let network = match self.network {
+            Some(NetworkStatus {
+                ssid,
+                signal: Some(signal),
+                ..
+            }) => format!("{ssid} ({signal} dBm)"),
+            Some(network) => network.ssid.clone(),
+            None => language.no_network().to_string(),
+        };
+        let ip = (self.network.and_then(|network| network.ip))
+            .map_or_else(|| "-".to_string(), |ip| ip.to_string());
+        let mut details = vec![
+            (language.failed_at(), time(self.time)),
+            (language.wifi(), network),
+            ("IP", ip),
+            ("Firmware", FIRMWARE_VERSION.to_string()),
+        ];
+        if let Some(retry) = self.retry {
+            details.push((language.next_attempt(), time(retry)));
+        }
+        details.push((
+            language.setup(),
+            format!("{} - {SETUP_URL}", self.access_point),
+        ));
+        details
+    }
+
+    /// clear the display and draw the report
+    pub fn draw<Display>(&self, display: &mut Display)
+    where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        display.clear(TriColor::White).unwrap();
+        let (width, height) = (DISPLAY_WIDTH as i32, DISPLAY_HEIGHT as i32);
+        let top_left = Point::new(Self::MARGIN, Self::MARGIN);
+        self.category.draw_icon(display, top_left, Self::ICON);
+        FontRenderer::new::<u8g2_font_helvB18_tf>()
+            .render_aligned(
+                self.category.title(self.language),
+                top_left + Point::new(Self::ICON as i32 + 12, Self::ICON as i32 / 2),
+                VerticalPosition::Center,
+                HorizontalAlignment::Left,
+                FontColor::Transparent(TriColor::Black),
+                display,
+            )
+            .unwrap();
+
+        // the details are placed from the bottom, the message takes the space in between
+        let details = self.details();
+        let details_top = height - Self::MARGIN - Self::ROW * details.len() as i32;
+        let message_top = Self::MARGIN + Self::ICON as i32 + 12;
+        let fonts = [
+            FontRenderer::new::<u8g2_font_helvB14_tf>(),
+            FontRenderer::new::<u8g2_font_helvB12_tf>(),
+            FontRenderer::new::<u8g2_font_helvB10_tf>(),
+            FontRenderer::new::<u8g2_font_helvR08_tf>(),
+        ];
+        TextBox {
+            area: Rectangle::new(
+                Point::new(Self::MARGIN, message_top),
+                Size::new(
+                    (width - 2 * Self::MARGIN) as u32,
+                    (details_top - 8 - message_top).max(0) as u32,
+                ),
+            ),
+            fonts: &fonts,
+            alignment: HorizontalAlignment::Left,
+            middle: false,
+            color: TriColor::Chromatic,
+        }
+        .draw(display, self.message);
+
+        let label_font = FontRenderer::new::<u8g2_font_helvB10_tf>();
+        let value_font = FontRenderer::new::<u8g2_font_helvR10_tf>();
+        for (i, (label, value)) in details.iter().enumerate() {
+            let y = details_top + Self::ROW * i as i32;
+            for (font, text, x) in [
+                (&label_font, *label, Self::MARGIN),
+                (&value_font, value.as_str(), Self::MARGIN + Self::LABEL),
+            ] {
+                font.render_aligned(
+                    text,
+                    Point::new(x, y),
+                    VerticalPosition::Top,
+                    HorizontalAlignment::Left,
+                    FontColor::Transparent(TriColor::Black),
+                    display,
+                )
+                .unwrap();
+            }
+        }
+
+        // the setup page as qr code right of the details, left out if the values leave no room
+        let widest = (details.iter())
+            .map(|(_, value)| text_width(&value_font, value) as i32)
+            .max()
+            .unwrap_or_default();
+        let free = width - 2 * Self::MARGIN - Self::LABEL - widest - 10;
+        let available = free.min(height - Self::MARGIN - details_top).max(0) as u32;
+        if let Ok(code) = QrCode::encode(SETUP_URL) {
+            if let Some(module_size) = code.module_size_for(available, QUIET_ZONE) {
+                let qr_width = code.width(module_size, QUIET_ZONE) as i32;
+                let top_left = Point::new(width - Self::MARGIN - qr_width, details_top);
+                code.draw(display, top_left, module_size, QUIET_ZONE);
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+use crate::constants::DISPLAY_WIDTH;
+
+/// part of the panel which can be refreshed on its own. `x` and `width` are multiples of 8,
+/// so every row of the region starts and ends on whole bytes of the frame buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// date, clock, battery and wifi at the top of the panel, refreshed between the forecasts
+pub const STATUS_STRIP: Region = Region::new(0, 0, DISPLAY_WIDTH, 48);
+
+impl Region {
+    /// the horizontal bounds are widened to the next byte boundaries
+    pub const fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        let start = x / 8 * 8;
+        let end = (x + width).div_ceil(8) * 8;
+        Self {
+            x: start,
+            y,
+            width: end - start,
+            height,
+        }
+    }
+
+    pub fn rectangle(&self) -> Rectangle {
+        Rectangle::new(
+            Point::new(self.x as i32, self.y as i32),
+            Size::new(self.width, self.height),
+        )
+    }
+
+    /// rows of the region cut out of one packed color plane of a panel `panel_width` pixels wide
+    pub fn cut(&self, plane: &[u8], panel_width: u32) -> Vec<u8> {
+        let stride = panel_width.div_ceil(8) as usize;
+        let start = (self.x / 8) as usize;
+        let length = (self.width / 8) as usize;
+        (self.y as usize..(self.y + self.height) as usize)
+            .filter_map(|row| plane.get(row * stride + start..row * stride + start + length))
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// black and chromatic plane of the region from a tri color frame buffer,
+    /// which holds the black plane followed by the chromatic one
+    pub fn planes(&self, buffer: &[u8], panel_width: u32) -> (Vec<u8>, Vec<u8>) {
+        let (black, chromatic) = buffer.split_at(buffer.len() / 2);
+        (
+            self.cut(black, panel_width),
+            self.cut(chromatic, panel_width),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshMode {
+    Full,
+    Partial,
+}
+
+/// counts the partial refreshes since the last full one. Partial refreshes leave ghosting,
+/// so after `max` of them the next refresh is a full one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PartialRefreshes {
+    count: u8,
+}
+
+impl PartialRefreshes {
+    pub fn next(&mut self, max: u8) -> RefreshMode {
+        if self.count < max {
+            self.count += 1;
+            RefreshMode::Partial
+        } else {
+            RefreshMode::Full
+        }
+    }
+
+    pub fn count(&self) -> u8 {
+        self.count
+    }
+}
@@ -0,0 +1,254 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+use serde::Deserialize;
+
+use crate::{
+    constants::{DISPLAY_HEIGHT, DISPLAY_WIDTH},
+    framebuffer::{Region, STATUS_STRIP},
+};
+
+/// rectangle of a widget in pixels from the top left corner of the panel
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Area {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Area {
+    pub const fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn rectangle(&self) -> Rectangle {
+        Rectangle::new(
+            Point::new(self.x as i32, self.y as i32),
+            Size::new(self.width, self.height),
+        )
+    }
+
+    /// y of the bottom edge, the first row below the area
+    pub fn bottom(&self) -> i32 {
+        (self.y + self.height) as i32
+    }
+
+    pub fn center_y(&self) -> i32 {
+        (self.y + self.height / 2) as i32
+    }
+}
+
+/// temperature line, scaled in celsius so the graph keeps its height in every unit
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct TemperatureGraph {
+    #[serde(flatten)]
+    pub area: Area,
+    /// temperature at the bottom of the area
+    pub min: f32,
+    /// temperature at the top of the area
+    pub max: f32,
+    /// hours between the temperature labels
+    #[serde(default = "every_third_hour")]
+    pub labels: usize,
+}
+
+/// precipitation bars with the probability line, the probability uses the whole height
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct RainGraph {
+    #[serde(flatten)]
+    pub area: Area,
+    /// pixels per millimeter of precipitation
+    #[serde(default = "ten_pixels")]
+    pub scale: f32,
+}
+
+/// a row repeated every few hours like the weather icons or the hour labels
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HourRow {
+    #[serde(flatten)]
+    pub area: Area,
+    pub every: usize,
+}
+
+fn every_third_hour() -> usize {
+    3
+}
+
+fn ten_pixels() -> f32 {
+    10.0
+}
+
+/// where the widgets are placed on the panel. Selected with the `LAYOUT` environment variable as json, only the widgets
+/// which differ from the default layout of the panel have to be given, like
+/// `{"hour_width": 8, "sun": {"x": 55, "y": 418, "width": 576, "height": 3}}`.
+///
+/// The lanes of the hourly graph share the time axis: every hour is `hour_width` pixels wide and each lane starts
+/// at its own `x`. As many hours are drawn as fit into the width of the lane.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Layout {
+    /// date, clock, battery and wifi. It is widened to whole bytes for the partial refresh.
+    pub status: Area,
+    /// weekday, date, condition and wind in one column per day
+    pub header: Area,
+    pub hour_width: u32,
+    pub temperature: TemperatureGraph,
+    pub rain: RainGraph,
+    /// bar from sunrise to sunset
+    pub sun: Area,
+    /// weather icons, the icons are drawn below the top of the area
+    pub icons: HourRow,
+    /// hour labels below a time axis along the top of the area
+    pub hours: HourRow,
+    /// red banner with the weather warnings on the three day graph, the other pages move down for it
+    pub warnings: Area,
+    /// air quality and pollen in one badge per day, only with air quality
+    pub air: Area,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    #[error("invalid layout: {0}")]
+    Parse(String),
+    #[error("the {0} is outside of the panel")]
+    OutsidePanel(&'static str),
+    #[error("the {0} is empty")]
+    Empty(&'static str),
+    #[error("the temperature range is empty")]
+    TemperatureRange,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::for_panel(DISPLAY_WIDTH, DISPLAY_HEIGHT)
+    }
+}
+
+impl Layout {
+    /// the three day graph below the status strip on a panel of `width` x `height` pixels. The lanes are placed
+    /// from the bottom of the panel, on small panels they shrink to the room below the warnings and air quality.
+    pub fn for_panel(width: u32, height: u32) -> Self {
+        let hours = (width - 60) / 10 * 10;
+        let header = Area::new(0, 50, width, 60);
+        let below = header.bottom() as u32 + 2;
+        let (warnings, air, temperature, rain) = if below + 80 <= height - 200 {
+            (
+                Area::new(0, below, width, 36),
+                Area::new(0, below + 40, width, 28),
+                Area::new(60, height - 185, hours, 50),
+                Area::new(60, height - 125, hours, 50),
+            )
+        } else {
+            let warnings = Area::new(0, below, width, 30);
+            let air = Area::new(0, warnings.bottom() as u32 + 2, width, 24);
+            // room for the temperature labels above the graph, the rain ends above the sun lane
+            let top = air.bottom() as u32 + 12;
+            let lane = (height - 64).saturating_sub(top + 4) / 2;
+            (
+                warnings,
+                air,
+                Area::new(60, top, hours, lane),
+                Area::new(60, top + lane + 4, hours, lane),
+            )
+        };
+        Self {
+            status: Area::new(STATUS_STRIP.x, STATUS_STRIP.y, width, STATUS_STRIP.height),
+            header,
+            hour_width: 10,
+            temperature: TemperatureGraph {
+                area: temperature,
+                min: -10.0,
+                max: 40.0,
+                labels: every_third_hour(),
+            },
+            rain: RainGraph {
+                area: rain,
+                scale: ten_pixels(),
+            },
+            sun: Area::new(55, height - 61, hours, 3),
+            icons: HourRow {
+                area: Area::new(55, height - 35, hours, 32),
+                every: 3,
+            },
+            hours: HourRow {
+                area: Area::new(55, height - 47, hours, 12),
+                every: 2,
+            },
+            warnings,
+            air,
+        }
+    }
+
+    /// parse and validate a json layout
+    pub fn from_json(json: &str) -> Result<Self, LayoutError> {
+        let layout = serde_json::from_str::<Self>(json)
+            .map_err(|err| LayoutError::Parse(err.to_string()))?;
+        layout.validate(DISPLAY_WIDTH, DISPLAY_HEIGHT)?;
+        Ok(layout)
+    }
+
+    pub fn from_env() -> Result<Self, LayoutError> {
+        option_env!("LAYOUT").map_or_else(|| Ok(Self::default()), Self::from_json)
+    }
+
+    /// check that every widget lies on a panel of `width` x `height` pixels
+    pub fn validate(&self, width: u32, height: u32) -> Result<(), LayoutError> {
+        let areas = [
+            ("status strip", self.status),
+            ("header", self.header),
+            ("temperature graph", self.temperature.area),
+            ("rain graph", self.rain.area),
+            ("sun lane", self.sun),
+            ("icon row", self.icons.area),
+            ("hour row", self.hours.area),
+            ("warning banner", self.warnings),
+            ("air quality", self.air),
+        ];
+        for (name, area) in areas {
+            if area.width == 0 || area.height == 0 {
+                return Err(LayoutError::Empty(name));
+            }
+            // an overflowing edge is far outside the panel
+            let inside = |start: u32, length: u32, end: u32| {
+                start.checked_add(length).is_some_and(|edge| edge <= end)
+            };
+            if !inside(area.x, area.width, width) || !inside(area.y, area.height, height) {
+                return Err(LayoutError::OutsidePanel(name));
+            }
+        }
+        if self.hour_width == 0 {
+            return Err(LayoutError::Empty("hour width"));
+        }
+        if self.icons.every == 0 || self.hours.every == 0 || self.temperature.labels == 0 {
+            return Err(LayoutError::Empty("hour interval"));
+        }
+        if self.temperature.min >= self.temperature.max {
+            return Err(LayoutError::TemperatureRange);
+        }
+        Ok(())
+    }
+
+    /// the status strip as region for the partial refresh
+    pub fn status_region(&self) -> Region {
+        Region::new(
+            self.status.x,
+            self.status.y,
+            self.status.width,
+            self.status.height,
+        )
+    }
+
+    /// number of hours which fit into the width of a lane
+    pub fn hours_in(&self, area: &Area) -> usize {
+        (area.width / self.hour_width) as usize
+    }
+
+    /// x of the hour `index` in a lane
+    pub fn hour_x(&self, area: &Area, index: usize) -> i32 {
+        area.x as i32 + (index as u32 * self.hour_width) as i32
+    }
+}
@@ -1,6 +1,35 @@
+pub mod battery;
+pub mod board;
+pub mod chart;
+pub mod clock;
+pub mod config;
 pub mod constants;
+pub mod diagnostics;
+pub mod framebuffer;
 pub mod icons;
 pub mod image_tri_color;
+pub mod layout;
+pub mod locale;
+pub mod network;
+pub mod pages;
+pub mod panel;
+#[cfg(target_os = "espidf")]
+pub mod partial;
+#[cfg(target_os = "espidf")]
+pub mod portal;
+#[cfg(target_os = "espidf")]
+pub mod power;
+pub mod provisioning;
+pub mod qr;
+pub mod schedule;
+pub mod secret;
+#[cfg(target_os = "espidf")]
+pub mod session;
+pub mod status;
+pub mod text;
+pub mod units;
+pub mod wake;
+pub mod warnings;
 pub mod weather;
 #[cfg(target_os = "espidf")]
 pub mod wifi;
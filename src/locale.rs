@@ -0,0 +1,163 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+
+/// Language used for all texts on the display. Selected with the `LANGUAGE` environment variable (`en` or `de`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_lowercase().as_str() {
+            "en" | "english" => Some(Self::English),
+            "de" | "german" | "deutsch" => Some(Self::German),
+            _ => None,
+        }
+    }
+
+    pub fn weekday(&self, weekday: Weekday) -> &'static str {
+        match self {
+            Self::English => match weekday {
+                Weekday::Mon => "Monday",
+                Weekday::Tue => "Tuesday",
+                Weekday::Wed => "Wednesday",
+                Weekday::Thu => "Thursday",
+                Weekday::Fri => "Friday",
+                Weekday::Sat => "Saturday",
+                Weekday::Sun => "Sunday",
+            },
+            Self::German => match weekday {
+                Weekday::Mon => "Montag",
+                Weekday::Tue => "Dienstag",
+                Weekday::Wed => "Mittwoch",
+                Weekday::Thu => "Donnerstag",
+                Weekday::Fri => "Freitag",
+                Weekday::Sat => "Samstag",
+                Weekday::Sun => "Sonntag",
+            },
+        }
+    }
+
+    /// abbreviated month name, `month` starts at 1 like [`Datelike::month`]
+    pub fn month(&self, month: u32) -> &'static str {
+        const ENGLISH: [&str; 12] = [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ];
+        const GERMAN: [&str; 12] = [
+            "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+        ];
+        let index = (month.clamp(1, 12) - 1) as usize;
+        match self {
+            Self::English => ENGLISH[index],
+            Self::German => GERMAN[index],
+        }
+    }
+
+    /// short date like `Mar 5, 24` or `5. Mär 24`
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        let month = self.month(date.month());
+        let year = date.year() % 100;
+        match self {
+            Self::English => format!("{} {}, {:02}", month, date.day(), year),
+            Self::German => format!("{}. {} {:02}", date.day(), month, year),
+        }
+    }
+
+    pub fn charge_battery(&self) -> &'static str {
+        match self {
+            Self::English => "Please charge the battery",
+            Self::German => "Bitte den Akku laden",
+        }
+    }
+
+    pub fn connect_to_setup(&self) -> &'static str {
+        match self {
+            Self::English => "Connect to the wifi to set up the display",
+            Self::German => "Zum Einrichten mit dem WLAN verbinden",
+        }
+    }
+
+    pub fn password(&self) -> &'static str {
+        match self {
+            Self::English => "Password",
+            Self::German => "Passwort",
+        }
+    }
+
+    pub fn open_setup_page(&self) -> &'static str {
+        match self {
+            Self::English => "and open the setup page",
+            Self::German => "und die Einrichtungsseite öffnen",
+        }
+    }
+
+    pub fn failed_at(&self) -> &'static str {
+        match self {
+            Self::English => "Failed",
+            Self::German => "Fehler",
+        }
+    }
+
+    pub fn next_attempt(&self) -> &'static str {
+        match self {
+            Self::English => "Next try",
+            Self::German => "Nächster Versuch",
+        }
+    }
+
+    pub fn wifi(&self) -> &'static str {
+        match self {
+            Self::English => "Wifi",
+            Self::German => "WLAN",
+        }
+    }
+
+    pub fn no_network(&self) -> &'static str {
+        match self {
+            Self::English => "not connected",
+            Self::German => "nicht verbunden",
+        }
+    }
+
+    pub fn setup(&self) -> &'static str {
+        match self {
+            Self::English => "Setup",
+            Self::German => "Einrichtung",
+        }
+    }
+
+    pub fn until_further_notice(&self) -> &'static str {
+        match self {
+            Self::English => "until further notice",
+            Self::German => "bis auf Weiteres",
+        }
+    }
+
+    pub fn no_pollen(&self) -> &'static str {
+        match self {
+            Self::English => "no pollen",
+            Self::German => "keine Pollen",
+        }
+    }
+
+    pub fn decimal_separator(&self) -> char {
+        match self {
+            Self::English => '.',
+            Self::German => ',',
+        }
+    }
+
+    pub fn format_decimal(&self, value: f32, precision: usize) -> String {
+        self.localize_number(&format!("{:.*}", precision, value))
+    }
+
+    /// replace the decimal point of an already formatted number like `0.4 mm`
+    pub fn localize_number(&self, formatted: &str) -> String {
+        match self.decimal_separator() {
+            '.' => formatted.to_string(),
+            separator => formatted.replace('.', separator.encode_utf8(&mut [0; 4])),
+        }
+    }
+}
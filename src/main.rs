@@ -1,44 +1,55 @@
-use chrono::Timelike;
-#[cfg(target_os = "espidf")]
-use chrono::{Days, NaiveTime};
-use embedded_graphics::{
-    prelude::*,
-    primitives::{Line, PrimitiveStyle, StyledDrawable},
-};
+use embedded_graphics::prelude::*;
 use epd_waveshare::color::TriColor;
 #[cfg(target_os = "espidf")]
-use epd_waveshare::{
-    epd7in5b_v2::Display7in5 as Display, epd7in5b_v2::Epd7in5 as Epd, prelude::WaveshareDisplay,
-};
+use epd_waveshare::prelude::WaveshareDisplay;
+#[cfg(all(target_os = "espidf", any(esp32, esp32s2, esp32s3)))]
+use esp_idf_svc::hal::spi::SPI3;
 #[cfg(target_os = "espidf")]
 use esp_idf_svc::{
     eventloop::EspSystemEventLoop,
     hal::{
         delay::Delay,
-        gpio::{AnyInputPin, IOPin, InputPin, OutputPin, PinDriver},
+        gpio::{AnyIOPin, AnyInputPin, AnyOutputPin, PinDriver},
         modem::WifiModem,
-        prelude::Peripherals,
-        spi::config::{Config, DriverConfig},
+        spi::{
+            config::{Config as SpiConfig, DriverConfig},
+            SpiDeviceDriver, SpiDriver, SPI2,
+        },
         units::Hertz,
     },
-    sntp::{EspSntp, SyncStatus},
+    nvs::EspDefaultNvsPartition,
 };
 #[cfg(target_os = "espidf")]
-use esp_weather::wifi;
 use esp_weather::{
-    constants::{DISPLAY_HEIGHT, DISPLAY_WIDTH, SECTION_WIDTH},
-    weather::WeatherForecast,
+    battery::draw_charge_screen,
+    board::{Board, Chip},
+    clock,
+    config::AccessPoint,
+    framebuffer::{PartialRefreshes, RefreshMode, Region},
+    pages::Page,
+    panel::{self, Canvas, Epd},
+    portal, power,
+    provisioning::{draw_provisioning_screen, generate_passphrase},
+    secret::Secret,
+    session::Session,
+    wake::{WakeAction, WakeCause},
+    wifi,
 };
-use smol::Executor;
-use std::time::Duration;
-use u8g2_fonts::{
-    fonts::{
-        u8g2_font_helvB10_tr, u8g2_font_helvR08_tf, u8g2_font_helvR08_tr,
-        u8g2_font_unifont_t_weather,
+use esp_weather::{
+    clock::TimeSource,
+    config::Config,
+    constants::{DISPLAY_HEIGHT, DISPLAY_WIDTH},
+    diagnostics::{ErrorCategory, ErrorReport},
+    network::NetworkStatus,
+    status::StatusStrip,
+    warnings::{Warning, WarningBanner, WarningSource},
+    weather::{
+        air_quality::{air_quality_url, AirQualityForecast},
+        forecast_url, WeatherForecast,
     },
-    types::{FontColor, VerticalPosition},
-    FontRenderer,
 };
+use smol::Executor;
+use std::time::Duration;
 
 fn main() {
     #[cfg(target_os = "espidf")]
@@ -55,9 +66,6 @@ fn main() {
             .unwrap();
     }
 
-    #[cfg(target_os = "espidf")]
-    let mut display = Box::new(Display::default());
-
     #[cfg(target_os = "linux")]
     tracing_subscriber::fmt().pretty().init();
 
@@ -69,385 +77,316 @@ fn main() {
         )),
     );
 
+    // without a valid wiring nothing can be shown, the error is only logged
+    #[cfg(target_os = "espidf")]
+    let board = match Board::from_env().and_then(|board| {
+        board.validate(&Chip {
+            gpio_count: esp_idf_svc::sys::gpio_num_t_GPIO_NUM_MAX as i32,
+            spi3: cfg!(any(esp32, esp32s2, esp32s3)),
+            rtc_pins: (0..esp_idf_svc::sys::gpio_num_t_GPIO_NUM_MAX)
+                .filter(|pin| unsafe { esp_idf_svc::sys::rtc_gpio_is_valid_gpio(*pin) })
+                .fold(0, |mask, pin| mask | (1 << pin)),
+        })?;
+        Ok(board)
+    }) {
+        Ok(board) => board,
+        Err(err) => {
+            log::error!("invalid board profile: {err}");
+            power::deep_sleep(None, None);
+        }
+    };
+    #[cfg(target_os = "espidf")]
+    power::wake_up(&board);
+
+    #[cfg(target_os = "espidf")]
+    let wake = WakeCause::read(&board);
+    #[cfg(target_os = "espidf")]
+    log::info!("woke up by {wake:?}");
+
+    #[cfg(target_os = "espidf")]
+    let nvs = EspDefaultNvsPartition::take().unwrap();
+
+    #[allow(unused_mut)]
+    let mut config = Config::from_env();
+    #[cfg(target_os = "espidf")]
+    match portal::load(&nvs) {
+        Ok(Some(settings)) => config.apply(settings),
+        Ok(None) => log::info!("not provisioned, using the compiled in settings"),
+        Err(err) => log::error!("failed to load the provisioning {err}"),
+    }
+
+    #[cfg(target_os = "espidf")]
+    let mut display = Box::new(Canvas::new(config.mono_red));
+
+    // one wifi session for the whole wake cycle, retries reuse the connection
+    #[cfg(target_os = "espidf")]
+    let mut session = Session::new(
+        unsafe { WifiModem::new() },
+        EspSystemEventLoop::take().unwrap(),
+        &config,
+    )
+    .unwrap();
+
+    #[cfg(target_os = "espidf")]
+    let mut attempts = 0;
+
     display.clear(TriColor::White).unwrap();
     let executor = Executor::new();
     executor
         .spawn(async move {
             loop {
-                match request_weather().await {
-                    Err(err) => {
+                #[cfg(target_os = "espidf")]
+                let battery = config.battery.as_ref().and_then(|battery| {
+                    let level = battery
+                        .read()
+                        .inspect_err(|err| log::error!("failed to read the battery {err}"))
+                        .ok()?;
+                    if battery.is_critical(&level) {
+                        // no wifi and no wake up timer, the device waits for a reset after charging
+                        draw_charge_screen(display.as_mut(), &level, config.language);
+                        update_display(&board, &display, None).unwrap_or_else(|err| {
+                            log::error!("failed to update the display {err}")
+                        });
+                        log::warn!("battery critical");
+                        sleep(&board, &mut session, None);
+                    }
+                    Some(level)
+                });
+                #[cfg(target_os = "linux")]
+                let battery: Option<esp_weather::battery::BatteryLevel> = None;
+
+                #[cfg(target_os = "espidf")]
+                if wake.action() == WakeAction::Provision {
+                    provision(
+                        &board,
+                        display.as_mut(),
+                        &config,
+                        nvs.clone(),
+                        session,
+                        None,
+                    );
+                }
+
+                // between the forecasts only the status strip is refreshed, without wifi
+                #[cfg(target_os = "espidf")]
+                if let Some(mut state) =
+                    unsafe { WAKE_STATE }.filter(|_| wake.action() == WakeAction::Scheduled)
+                {
+                    let now = chrono::Utc::now();
+                    if let Some(interval) = config
+                        .status_interval
+                        .filter(|_| panel::PARTIAL && now < state.forecast)
+                    {
+                        if state.refreshes.next(config.max_partial_refreshes)
+                            == RefreshMode::Partial
                         {
-                            // center the error message
-                            let error = err.to_string();
-                            FontRenderer::new::<u8g2_font_helvB10_tr>()
-                                .render_aligned(
-                                    error.as_str(),
-                                    Point::new(DISPLAY_WIDTH as i32 / 2, DISPLAY_HEIGHT as i32 / 2),
-                                    VerticalPosition::Baseline,
-                                    u8g2_fonts::types::HorizontalAlignment::Center,
-                                    FontColor::Transparent(TriColor::Black),
-                                    display.as_mut(),
-                                )
-                                .unwrap();
-                            log::error!("error occured {}", error);
-                            smol::Timer::after(Duration::from_secs(10)).await;
-                            continue;
+                            let network = wifi::last_network();
+                            let region = config.layout.status_region();
+                            StatusStrip {
+                                region,
+                                time: now.with_timezone(&state.timezone).naive_local(),
+                                language: config.language,
+                                time_source: state.time_source,
+                                battery,
+                                network: network.as_ref(),
+                                mono: true,
+                            }
+                            .draw(display.as_mut());
+                            update_display(&board, &display, Some(&region)).unwrap_or_else(|err| {
+                                log::error!("failed to update the display {err}")
+                            });
+                            log::info!("partial refresh {}", state.refreshes.count());
+
+                            unsafe { WAKE_STATE = Some(state) };
+                            sleep(
+                                &board,
+                                &mut session,
+                                Some(state.forecast.min(now + interval)),
+                            );
                         }
-                        // log::error!("error: {err:?}")
                     }
-                    Ok(weather) => {
-                        // write the day
-                        let today = chrono::Local::now();
-                        let today = format!("{}", today.format("%e. %b %y"));
-                        FontRenderer::new::<u8g2_font_helvB10_tr>()
-                            .render_aligned(
-                                today.as_str(),
-                                Point::new(DISPLAY_WIDTH as i32 / 2, 30),
-                                VerticalPosition::Baseline,
-                                u8g2_fonts::types::HorizontalAlignment::Center,
-                                FontColor::Transparent(TriColor::Black),
-                                display.as_mut(),
-                            )
-                            .unwrap();
-
-                        // weather
-                        //     .weather
-                        //     .sort_by(|a, b| a.date.value().cmp(&b.date.value()));
-
-                        for (i, day) in weather.daily.time.iter().enumerate() {
-                            let x = (SECTION_WIDTH * (i as i32)) + SECTION_WIDTH / 2;
-                            FontRenderer::new::<u8g2_font_helvB10_tr>()
-                                .render_aligned(
-                                    format!("{}", day.0.format("%A")).as_str(),
-                                    Point::new(x, 50 + 10),
-                                    VerticalPosition::Baseline,
-                                    u8g2_fonts::types::HorizontalAlignment::Center,
-                                    FontColor::Transparent(TriColor::Black),
-                                    display.as_mut(),
-                                )
-                                .unwrap();
-
-                            FontRenderer::new::<u8g2_font_helvR08_tr>()
-                                .render_aligned(
-                                    format!("{}", day.0.format("%e. %b %y")).as_str(),
-                                    Point::new(x, 50 + 25),
-                                    VerticalPosition::Baseline,
-                                    u8g2_fonts::types::HorizontalAlignment::Center,
-                                    FontColor::Transparent(TriColor::Black),
-                                    display.as_mut(),
-                                )
-                                .unwrap();
-                        }
-
-                        let mut last_rain_point = Point::zero();
-                        let mut last_temp_point = Point::zero();
+                }
 
-                        let font = FontRenderer::new::<u8g2_font_helvR08_tr>();
+                #[cfg(target_os = "espidf")]
+                if config.networks.is_empty() {
+                    provision(
+                        &board,
+                        display.as_mut(),
+                        &config,
+                        nvs.clone(),
+                        session,
+                        None,
+                    );
+                }
 
-                        FontRenderer::new::<u8g2_font_unifont_t_weather>()
-                            .render_aligned(
-                                String::from_utf8([49].to_vec()).unwrap().as_str(),
-                                Point::new(0, DISPLAY_HEIGHT as i32 - 160),
-                                VerticalPosition::Center,
-                                u8g2_fonts::types::HorizontalAlignment::Left,
-                                FontColor::Transparent(TriColor::Black),
-                                display.as_mut(),
+                let (timezone, time_source) = match request_weather(
+                    &config,
+                    #[cfg(target_os = "espidf")]
+                    &mut session,
+                )
+                .await
+                {
+                    Err(err) => {
+                        let error = format!("{err:#}");
+                        log::error!("error occured {}", error);
+
+                        // show the error and try again with the next refresh instead of draining the battery
+                        let now = chrono::Utc::now();
+                        #[cfg(target_os = "espidf")]
+                        let (timezone, last_attempt, network) = {
+                            attempts += 1;
+                            (
+                                unsafe { WAKE_STATE }
+                                    .map_or(chrono_tz::UTC, |state| state.timezone),
+                                attempts >= MAX_ATTEMPTS,
+                                session.status().cloned().or_else(wifi::last_network),
                             )
-                            .unwrap();
-                        font.render_aligned(
-                            "C",
-                            Point::new(18, DISPLAY_HEIGHT as i32 - 165),
-                            VerticalPosition::Center,
-                            u8g2_fonts::types::HorizontalAlignment::Left,
-                            FontColor::Transparent(TriColor::Black),
-                            display.as_mut(),
-                        )
-                        .unwrap();
-
-                        for (i, temp) in weather.hourly.temperature_2m.into_iter().enumerate() {
-                            // temperature graph
-                            let temperature = temp as i32 + 10;
-
-                            // temperature
-                            let current_point = Point::new(
-                                (i as i32 * 10) + 60,
-                                DISPLAY_HEIGHT as i32 - 135 - temperature,
-                            );
-
-                            if i % 3 == 0 {
-                                let text_position = current_point - Point::new(0, 10);
-                                let font = FontRenderer::new::<u8g2_font_helvR08_tf>();
-                                font.render_aligned(
-                                    format!("{}", temp as i32).as_str(),
-                                    text_position,
-                                    VerticalPosition::Center,
-                                    u8g2_fonts::types::HorizontalAlignment::Center,
-                                    FontColor::Transparent(TriColor::Black),
+                        };
+                        #[cfg(target_os = "linux")]
+                        let (timezone, last_attempt, network) =
+                            (chrono_tz::UTC, false, None::<NetworkStatus>);
+                        let retry = if last_attempt {
+                            config.refresh.next_wake(now, &timezone)
+                        } else {
+                            now + RETRY_DELAY
+                        };
+                        let category = error_category(&err);
+                        ErrorReport {
+                            category,
+                            message: &error,
+                            time: now.with_timezone(&timezone).naive_local(),
+                            network: network.as_ref(),
+                            retry: Some(retry.with_timezone(&timezone).naive_local()),
+                            access_point: &config.provisioning.ssid,
+                            language: config.language,
+                        }
+                        .draw(display.as_mut());
+
+                        #[cfg(target_os = "espidf")]
+                        if last_attempt {
+                            // the stored network may have changed, after some refreshes without wifi the portal
+                            // opens until the next refresh
+                            let failures = match category {
+                                ErrorCategory::Network => unsafe { NETWORK_FAILURES + 1 },
+                                _ => 0,
+                            };
+                            unsafe { NETWORK_FAILURES = failures % PORTAL_AFTER_FAILURES };
+                            if failures >= PORTAL_AFTER_FAILURES {
+                                log::warn!(
+                                    "no wifi for {failures} refreshes, starting provisioning"
+                                );
+                                provision(
+                                    &board,
                                     display.as_mut(),
-                                )
-                                .unwrap();
+                                    &config,
+                                    nvs.clone(),
+                                    session,
+                                    Some(retry),
+                                );
                             }
-
-                            if last_temp_point == Point::zero() {
-                                last_temp_point = current_point;
-                                continue;
-                            }
-
-                            Line::new(last_temp_point, current_point)
-                                .into_styled(PrimitiveStyle::with_stroke(TriColor::Black, 1))
-                                .draw(display.as_mut())
-                                .unwrap();
-
-                            last_temp_point = current_point;
+                            update_display(&board, &display, None).unwrap_or_else(|err| {
+                                log::error!("failed to update the display {err}")
+                            });
+                            sleep(&board, &mut session, Some(retry));
                         }
-                        FontRenderer::new::<u8g2_font_unifont_t_weather>()
-                            .render_aligned(
-                                String::from_utf8([55].to_vec()).unwrap().as_str(),
-                                Point::new(0, DISPLAY_HEIGHT as i32 - 110),
-                                VerticalPosition::Center,
-                                u8g2_fonts::types::HorizontalAlignment::Left,
-                                FontColor::Transparent(TriColor::Chromatic),
-                                display.as_mut(),
-                            )
-                            .unwrap();
-                        font.render_aligned(
-                            "%",
-                            Point::new(18, DISPLAY_HEIGHT as i32 - 115),
-                            VerticalPosition::Center,
-                            u8g2_fonts::types::HorizontalAlignment::Left,
-                            FontColor::Transparent(TriColor::Chromatic),
-                            display.as_mut(),
-                        )
-                        .unwrap();
-
-                        FontRenderer::new::<u8g2_font_unifont_t_weather>()
-                            .render_aligned(
-                                String::from_utf8([55].to_vec()).unwrap().as_str(),
-                                Point::new(0, DISPLAY_HEIGHT as i32 - 90),
-                                VerticalPosition::Center,
-                                u8g2_fonts::types::HorizontalAlignment::Left,
-                                FontColor::Transparent(TriColor::Black),
-                                display.as_mut(),
-                            )
-                            .unwrap();
-                        font.render_aligned(
-                            "mm",
-                            Point::new(18, DISPLAY_HEIGHT as i32 - 95),
-                            VerticalPosition::Center,
-                            u8g2_fonts::types::HorizontalAlignment::Left,
-                            FontColor::Transparent(TriColor::Black),
-                            display.as_mut(),
+                        smol::Timer::after(RETRY_DELAY.to_std().unwrap()).await;
+                        continue;
+                    }
+                    Ok((mut weather, network, time_source)) => {
+                        #[cfg(target_os = "espidf")]
+                        unsafe {
+                            NETWORK_FAILURES = 0;
+                        }
+                        let timezone = weather
+                            .timezone
+                            .parse::<chrono_tz::Tz>()
+                            .unwrap_or(chrono_tz::UTC);
+                        if let Some(air_quality) = request_air_quality(
+                            &config,
+                            #[cfg(target_os = "espidf")]
+                            &mut session,
                         )
-                        .unwrap();
-                        let mut last_precipitation = 0.0f32;
-                        let mut last_drawn = false;
-                        for (i, precipitation) in
-                            weather.hourly.precipitation.into_iter().enumerate()
+                        .await
                         {
-                            // rain
-                            let x = (i as i32 * 10) + 60;
-                            let y = DISPLAY_HEIGHT as i32 - 75;
-                            let rain_precipation = (precipitation * 10.0) as i32;
-                            if last_precipitation != 0.0 && !last_drawn {
-                                let current_precipation = last_precipitation.max(precipitation);
-                                let x = if precipitation == 0.0 { x - 10 } else { x - 5 };
-                                font.render_aligned(
-                                    format!("{:?}", current_precipation).as_str(),
-                                    Point::new(x, y - (current_precipation as i32 * 10) - 10),
-                                    VerticalPosition::Center,
-                                    u8g2_fonts::types::HorizontalAlignment::Center,
-                                    FontColor::Transparent(TriColor::Black),
-                                    display.as_mut(),
-                                )
-                                .unwrap();
-                                last_drawn = true;
-                            } else {
-                                last_drawn = false;
-                            }
-                            last_precipitation = precipitation;
-
-                            // rain graph
-                            if precipitation <= 0.0 {
-                                continue;
-                            }
-
-                            // rain probability
-                            let current_point = Point::new(x, y - rain_precipation);
-
-                            Line::new(Point::new(x, y), current_point)
-                                .into_styled(PrimitiveStyle::with_stroke(TriColor::Black, 10))
-                                .draw(display.as_mut())
-                                .unwrap();
+                            weather.merge_air_quality(&air_quality);
                         }
-                        for (i, precipitation_probability) in weather
-                            .hourly
-                            .precipitation_probability
-                            .into_iter()
-                            .enumerate()
-                        {
-                            let rain_probability = (precipitation_probability as i32) / 2;
-                            let x = (i as i32 * 10) + 60;
-                            let y = DISPLAY_HEIGHT as i32 - 75 - rain_probability;
-                            if last_rain_point == Point::zero() {
-                                last_rain_point = Point::new(x, y);
-                                continue;
-                            }
-                            // rain probability
-                            let current_point = Point::new(x, y);
-
-                            Line::new(last_rain_point, current_point)
-                                .into_styled(PrimitiveStyle::with_stroke(TriColor::Chromatic, 1))
-                                .draw(display.as_mut())
-                                .unwrap();
-
-                            last_rain_point = current_point;
+                        let warnings = request_warnings(
+                            &config,
+                            #[cfg(target_os = "espidf")]
+                            &mut session,
+                        )
+                        .await;
+
+                        // an error screen of a failed attempt may still be in the frame buffer
+                        display.clear(TriColor::White).unwrap();
+                        let now = chrono::Utc::now().with_timezone(&timezone).naive_local();
+                        StatusStrip {
+                            region: config.layout.status_region(),
+                            time: now,
+                            language: config.language,
+                            time_source,
+                            battery,
+                            network: network.as_ref(),
+                            mono: false,
                         }
-                        for (i, (sunrise, sunset)) in (weather.daily.sunrise)
-                            .iter()
-                            .zip(&(weather.daily).sunset)
-                            .enumerate()
-                        {
-                            // sunrist and sunset
-                            let sunrise = sunrise.0.hour() * 10 + (sunrise.0.minute() / 10);
-                            let sunset = sunset.0.hour() * 10 + (sunset.0.minute() / 10);
-
-                            let offset = (i * 10 * 24) as i32 + (60 - 5);
-
-                            let sunrise_point =
-                                Point::new(sunrise as i32 + offset, DISPLAY_HEIGHT as i32 - 60);
-                            let sunset_point =
-                                Point::new(sunset as i32 + offset, DISPLAY_HEIGHT as i32 - 60);
-
-                            Line::new(sunrise_point, sunset_point)
-                                .draw_styled(
-                                    &PrimitiveStyle::with_stroke(TriColor::Chromatic, 3),
-                                    display.as_mut(),
-                                )
-                                .unwrap();
-
-                            // Line::new(sunrise_point, sunset_point)
-                            //     .into_styled(PrimitiveStyle::with_stroke(TriColor::Chromatic, 2))
-                            //     .draw(display.as_mut())
-                            //     .unwrap();
+                        .draw(display.as_mut());
+
+                        #[cfg(target_os = "espidf")]
+                        let (last, next_page) =
+                            (unsafe { PAGE }, wake.action() == WakeAction::NextPage);
+                        #[cfg(target_os = "linux")]
+                        let (last, next_page) = (None, false);
+                        let page = config.pages.select(last, next_page, now.time());
+                        log::info!("showing {page:?}");
+                        page.draw(
+                            display.as_mut(),
+                            &weather,
+                            config.language,
+                            &config.layout,
+                            now,
+                            !warnings.is_empty(),
+                        );
+                        WarningBanner {
+                            area: page.banner(&config.layout),
+                            warnings: &warnings,
+                            timezone,
+                            language: config.language,
                         }
-
-                        for (i, time) in weather.hourly.time.iter().enumerate() {
-                            // hours
-                            if i % 2 == 0 {
-                                let text = time.0.hour().to_string();
-                                font.render_aligned(
-                                    text.as_str(),
-                                    Point::new(
-                                        (i as i32 * 10) + (60 - 5),
-                                        DISPLAY_HEIGHT as i32 - 35,
-                                    ),
-                                    VerticalPosition::Bottom,
-                                    u8g2_fonts::types::HorizontalAlignment::Center,
-                                    FontColor::Transparent(TriColor::Black),
-                                    display.as_mut(),
-                                )
-                                .unwrap();
-                            }
+                        .draw(display.as_mut());
+                        #[cfg(target_os = "espidf")]
+                        unsafe {
+                            PAGE = Some(page);
                         }
 
-                        for (i, ((code, time), cloud_coverage)) in weather
-                            .hourly
-                            .weather_code
-                            .iter()
-                            .zip(weather.hourly.time)
-                            .zip(weather.hourly.cloud_cover)
-                            .enumerate()
-                        {
-                            if i % 3 == 0 {
-                                if let Some(((_date, sunrise), sunset)) = weather
-                                    .daily
-                                    .time
-                                    .iter()
-                                    .zip(&weather.daily.sunrise)
-                                    .zip(&weather.daily.sunset)
-                                    .find(|((date, _), _)| time.0.date().eq(&date.0))
-                                {
-                                    let is_day = time.0 >= sunrise.0 && time.0 <= sunset.0;
-                                    code.draw_icon(
-                                        display.as_mut(),
-                                        (i as i32 * 10) + (60 - 5),
-                                        DISPLAY_HEIGHT as i32 - 35,
-                                        cloud_coverage,
-                                        is_day,
-                                    );
-                                }
-                            }
-                        }
+                        (timezone, time_source)
                     }
                 };
 
+                let now = chrono::Utc::now();
+                let wake_up = config.refresh.next_wake(now, &timezone);
+                log::info!("next refresh at {}", wake_up.with_timezone(&timezone));
+
                 #[cfg(target_os = "espidf")]
                 {
-                    const SPI_FREQUENCY: u32 = 5_000_000;
-
-                    let peripherals = Peripherals::take().unwrap();
-
-                    // setup display
-                    let spi = peripherals.spi2;
-                    let sclk = peripherals.pins.gpio21.downgrade_output();
-                    let sdin = peripherals.pins.gpio19.downgrade_output();
-                    let cs = peripherals.pins.gpio18.downgrade_output();
-
-                    let driver_config = DriverConfig::default();
-                    let config = Config {
-                        baudrate: Hertz(SPI_FREQUENCY),
-                        // bit_order: BitOrder::MsbFirst,
-                        // write_only: true,
-                        ..Default::default()
-                    };
-
-                    let spi_driver = esp_idf_svc::hal::spi::SpiDriver::new(
-                        spi,
-                        sclk,
-                        sdin,
-                        AnyInputPin::none(),
-                        &driver_config,
-                    )
-                    .unwrap();
-
-                    let mut spi =
-                        esp_idf_svc::hal::spi::SpiDeviceDriver::new(spi_driver, Some(cs), &config)
-                            .unwrap();
-
-                    let mut pwr = PinDriver::input_output(peripherals.pins.gpio0).unwrap();
-                    pwr.set_high().unwrap();
-
-                    let busy = PinDriver::input(peripherals.pins.gpio1.downgrade_input()).unwrap();
-                    let rst = PinDriver::input_output(peripherals.pins.gpio2.downgrade()).unwrap();
-                    let dc = PinDriver::input_output(peripherals.pins.gpio3.downgrade()).unwrap();
-
-                    let mut delay = Delay::new_default();
-
-                    println!("pre drawing");
-                    let mut epd = Epd::new(&mut spi, busy, dc, rst, &mut delay, None).unwrap();
-                    epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)
-                        .unwrap();
-
-                    log::info!("finished drawing");
-
-                    epd.sleep(&mut spi, &mut delay).unwrap();
-
-                    log::info!("going to sleep for ");
-
-                    let tomorrow = chrono::Local::now()
-                        .checked_add_days(Days::new(1))
-                        .unwrap()
-                        .with_time(NaiveTime::default())
-                        .unwrap();
+                    update_display(&board, &display, None)
+                        .unwrap_or_else(|err| log::error!("failed to update the display {err}"));
 
-                    let until_tomorrow_time = tomorrow.signed_duration_since(chrono::Local::now());
-
-                    log::warn!("sleeping now for {}", until_tomorrow_time.to_string());
                     unsafe {
-                        esp_idf_svc::sys::esp_deep_sleep(
-                            until_tomorrow_time.num_microseconds().unwrap() as u64,
-                        )
+                        WAKE_STATE = Some(WakeState {
+                            forecast: wake_up,
+                            timezone,
+                            time_source,
+                            refreshes: Default::default(),
+                        })
                     };
+                    sleep(
+                        &board,
+                        &mut session,
+                        Some(match config.status_interval {
+                            Some(interval) => wake_up.min(now + interval),
+                            None => wake_up,
+                        }),
+                    );
                 }
                 #[cfg(target_os = "linux")]
                 {
@@ -484,61 +423,294 @@ fn main() {
     }
 }
 
+/// forecast of the last full refresh, kept in the rtc memory during the deep sleep
+#[cfg(target_os = "espidf")]
+#[derive(Clone, Copy)]
+struct WakeState {
+    /// when the next forecast is fetched
+    forecast: chrono::DateTime<chrono::Utc>,
+    timezone: chrono_tz::Tz,
+    time_source: TimeSource,
+    refreshes: PartialRefreshes,
+}
+
+#[cfg(target_os = "espidf")]
+#[link_section = ".rtc.data"]
+static mut WAKE_STATE: Option<WakeState> = None;
+
+/// page shown with the last forecast, the button and the rotation continue from it
+#[cfg(target_os = "espidf")]
+#[link_section = ".rtc.data"]
+static mut PAGE: Option<Page> = None;
+
+/// failed forecast requests before the error is shown until the next refresh
+#[cfg(target_os = "espidf")]
+const MAX_ATTEMPTS: u32 = 3;
+
+/// refreshes in a row which failed to connect to the wifi
+#[cfg(target_os = "espidf")]
+#[link_section = ".rtc.data"]
+static mut NETWORK_FAILURES: u32 = 0;
+
+/// refreshes without wifi before the provisioning portal opens on its own
+#[cfg(target_os = "espidf")]
+const PORTAL_AFTER_FAILURES: u32 = 2;
+
+/// how long the portal opened after failed refreshes waits for new settings
+#[cfg(target_os = "espidf")]
+const PORTAL_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// pause between two failed forecast requests
+const RETRY_DELAY: chrono::TimeDelta = chrono::TimeDelta::seconds(10);
+
+/// send the frame buffer to the e-paper and put the panel to sleep. With a `region` only that part of the panel is refreshed.
+#[cfg(target_os = "espidf")]
+fn update_display(board: &Board, display: &Canvas, region: Option<&Region>) -> anyhow::Result<()> {
+    // the pins are checked by `Board::validate` at startup
+    let output = |pin| unsafe { AnyOutputPin::new(pin) };
+    let io = |pin| unsafe { AnyIOPin::new(pin) };
+
+    // setup display
+    let driver_config = DriverConfig::default();
+    let spi_config = SpiConfig {
+        baudrate: Hertz(board.frequency),
+        // bit_order: BitOrder::MsbFirst,
+        // write_only: true,
+        ..Default::default()
+    };
+
+    let (sclk, sdin) = (output(board.sclk), output(board.mosi));
+    let spi_driver = match board.spi_host {
+        #[cfg(any(esp32, esp32s2, esp32s3))]
+        3 => SpiDriver::new(
+            unsafe { SPI3::new() },
+            sclk,
+            sdin,
+            AnyInputPin::none(),
+            &driver_config,
+        )?,
+        _ => SpiDriver::new(
+            unsafe { SPI2::new() },
+            sclk,
+            sdin,
+            AnyInputPin::none(),
+            &driver_config,
+        )?,
+    };
+
+    let mut spi = SpiDeviceDriver::new(spi_driver, Some(output(board.cs)), &spi_config)?;
+
+    let mut pwr = board
+        .power
+        .map(|pin| PinDriver::output(output(pin)))
+        .transpose()?;
+    if let Some(pwr) = &mut pwr {
+        pwr.set_level(board.power_active_high.into())?;
+    }
+
+    let mut busy = PinDriver::input(unsafe { AnyInputPin::new(board.busy) })?;
+    let mut rst = PinDriver::input_output(io(board.reset))?;
+    let mut dc = PinDriver::input_output(io(board.dc))?;
+
+    let mut delay = Delay::new_default();
+    let epd_error = |err| anyhow::anyhow!("e-paper error {err:?}");
+
+    let mut epd =
+        Epd::new(&mut spi, &mut busy, &mut dc, &mut rst, &mut delay, None).map_err(epd_error)?;
+    match region.filter(|_| panel::PARTIAL) {
+        None => {
+            epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)
+                .map_err(epd_error)?;
+            epd.sleep(&mut spi, &mut delay).map_err(epd_error)?;
+        }
+        #[cfg(feature = "panel-4in2")]
+        Some(region) => {
+            epd.update_partial_frame(
+                &mut spi,
+                &mut delay,
+                &region.cut(display.buffer(), DISPLAY_WIDTH),
+                region.x,
+                region.y,
+                region.width,
+                region.height,
+            )
+            .map_err(epd_error)?;
+            epd.display_frame(&mut spi, &mut delay).map_err(epd_error)?;
+            epd.sleep(&mut spi, &mut delay).map_err(epd_error)?;
+        }
+        #[cfg(not(feature = "panel-4in2"))]
+        Some(region) => {
+            // the driver has no partial refresh for the red panels, the pins are borrowed back after the init
+            drop(epd);
+            let (black, _) = region.planes(display.buffer(), DISPLAY_WIDTH);
+            esp_weather::partial::PartialUpdate {
+                spi: &mut spi,
+                busy: &mut busy,
+                dc: &mut dc,
+            }
+            .update_and_sleep(region, &black)?;
+        }
+    }
+
+    log::info!("finished drawing");
+    Ok(())
+}
+
+/// stop the wifi and sleep until `until`, or until reset without it
+#[cfg(target_os = "espidf")]
+fn sleep(board: &Board, session: &mut Session, until: Option<chrono::DateTime<chrono::Utc>>) -> ! {
+    if let Err(err) = session.disconnect() {
+        log::warn!("failed to stop the wifi {err}");
+    }
+    power::deep_sleep(Some(board), until)
+}
+
+/// show how to reach the provisioning form and serve it until the settings are saved and the device restarts.
+/// A portal opened after failed refreshes closes after `PORTAL_TIMEOUT` and the device sleeps until `fallback`.
+#[cfg(target_os = "espidf")]
+fn provision(
+    board: &Board,
+    display: &mut Canvas,
+    config: &Config,
+    nvs: EspDefaultNvsPartition,
+    session: Session,
+    fallback: Option<chrono::DateTime<chrono::Utc>>,
+) -> ! {
+    // the form receives the wifi password, so the access point is never open
+    let access_point = AccessPoint {
+        ssid: config.provisioning.ssid.clone(),
+        password: Some(config.provisioning.password.clone().unwrap_or_else(|| {
+            Secret::new(generate_passphrase(|| unsafe {
+                esp_idf_svc::sys::esp_random()
+            }))
+        })),
+    };
+    draw_provisioning_screen(display, &access_point, config.language);
+    update_display(board, display, None)
+        .unwrap_or_else(|err| log::error!("failed to update the display {err}"));
+
+    // the portal needs its own wifi driver with an access point
+    let sysloop = session.close();
+    let modem = unsafe { WifiModem::new() };
+    let timeout = fallback.map(|_| PORTAL_TIMEOUT);
+    if let Err(err) = portal::run(modem, sysloop, nvs, &access_point, timeout) {
+        log::error!("provisioning failed {err}");
+    }
+    match fallback {
+        Some(until) => power::deep_sleep(Some(board), Some(until)),
+        None => esp_idf_svc::hal::reset::restart(),
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 enum WeatherError {
     #[error("Got no weather")]
     NoWeather,
+    #[cfg(target_os = "espidf")]
+    #[error("Could not connect to the wifi: {0}")]
+    NoWifi(anyhow::Error),
+    #[cfg(target_os = "espidf")]
+    #[error("Could not get the time")]
+    NoTime,
 }
 
-async fn request_weather() -> anyhow::Result<WeatherForecast> {
-    let result;
-    let url = "https://api.open-meteo.com/v1/forecast?latitude=50.1155&longitude=8.6842&hourly=temperature_2m,precipitation_probability,precipitation,weather_code,cloud_cover&daily=sunrise,sunset&timezone=Europe%2FBerlin&forecast_days=3".to_string();
+/// kind of a failed forecast request for the error screen
+fn error_category(err: &anyhow::Error) -> ErrorCategory {
+    match err.downcast_ref::<WeatherError>() {
+        #[cfg(target_os = "espidf")]
+        Some(WeatherError::NoWifi(_)) => ErrorCategory::Network,
+        #[cfg(target_os = "espidf")]
+        Some(WeatherError::NoTime) => ErrorCategory::Time,
+        Some(WeatherError::NoWeather) => ErrorCategory::Service,
+        None if err.is::<serde_json::Error>() => ErrorCategory::Data,
+        None => ErrorCategory::Service,
+    }
+}
 
+/// body of an optional request besides the forecast
+async fn get(
+    url: &str,
+    #[cfg(target_os = "espidf")] session: &mut Session,
+) -> anyhow::Result<Vec<u8>> {
     #[cfg(target_os = "espidf")]
-    {
-        const SSID: &str = env!("SSID");
-        const PASS: &str = env!("PASS");
-        let sysloop = EspSystemEventLoop::take().unwrap();
-
-        let modem = unsafe { WifiModem::new() };
-        println!("ssid: {}, pw: {}", SSID, PASS);
-        let (_esp_wifi, _) = wifi::wifi(modem, sysloop, SSID, PASS).unwrap();
-
-        let ntp = Box::new(EspSntp::new_default().unwrap());
+    let body = session.get(url)?.body;
+    #[cfg(target_os = "linux")]
+    let body = reqwest::get(url).await?.bytes().await?.to_vec();
+    Ok(body)
+}
 
-        while ntp.get_sync_status() != SyncStatus::Completed {
-            smol::Timer::after(Duration::from_millis(200)).await;
+/// the current warnings of the configured region. Without them the forecast is still shown, so errors are only logged.
+async fn request_warnings(
+    config: &Config,
+    #[cfg(target_os = "espidf")] session: &mut Session,
+) -> Vec<Warning> {
+    let Some(source) = &config.warnings else {
+        return Vec::new();
+    };
+    let warnings = get(
+        &source.url(),
+        #[cfg(target_os = "espidf")]
+        session,
+    )
+    .await
+    .and_then(|body| source.parse(&body));
+    let now = chrono::Utc::now();
+    match warnings {
+        Ok(warnings) => (warnings.into_iter())
+            .filter(|warning| warning.is_current(now))
+            .collect(),
+        Err(err) => {
+            log::error!("failed to get the warnings {err:#}");
+            Vec::new()
         }
-        // setup epd done
-        let config = esp_idf_svc::http::client::Configuration {
-            crt_bundle_attach: Some(esp_idf_svc::sys::esp_crt_bundle_attach),
-            ..Default::default()
-        };
-        let connection = esp_idf_svc::http::client::EspHttpConnection::new(&config).unwrap();
-
-        let mut client = embedded_svc::http::client::Client::wrap(connection);
-        // Prepare headers and URL
-        let headers = [("Accept", "application/json")];
-
-        // Send request
-        //
-        // Note: If you don't want to pass in any headers, you can also use `client.get(url, headers)`.
-        log::info!("starting request");
-        let request = client.request(embedded_svc::http::Method::Get, url.as_str(), &headers)?;
-        let mut response = request.submit()?;
+    }
+}
 
-        // Process response
-        let status = response.status();
-        log::info!("status: {status}");
+/// the air quality and pollen if the location asks for them, errors are only logged
+async fn request_air_quality(
+    config: &Config,
+    #[cfg(target_os = "espidf")] session: &mut Session,
+) -> Option<AirQualityForecast> {
+    if !config.location.air_quality {
+        return None;
+    }
+    get(
+        &air_quality_url(&config.location),
+        #[cfg(target_os = "espidf")]
+        session,
+    )
+    .await
+    .and_then(|body| Ok(serde_json::from_slice(&body)?))
+    .inspect_err(|err| log::error!("failed to get the air quality {err:#}"))
+    .ok()
+}
 
-        // log::info!("size {}", size);
-        let mut buffer = Box::new([0; 1 << 16]);
+/// the forecast, the network used to fetch it and where the current time came from
+async fn request_weather(
+    config: &Config,
+    #[cfg(target_os = "espidf")] session: &mut Session,
+) -> anyhow::Result<(WeatherForecast, Option<NetworkStatus>, TimeSource)> {
+    let result;
+    let network;
+    let time;
+    let url = forecast_url(&config.location, &config.units);
 
-        let bytes_read = response.read(buffer.as_mut())?;
+    #[cfg(target_os = "espidf")]
+    {
+        network = Some(session.connect().map_err(WeatherError::NoWifi)?.clone());
+        let synced = session.sync_time().await;
 
-        let string = std::str::from_utf8(&buffer[0..bytes_read])?;
+        let response = session.get(url.as_str())?;
+        result = Some(serde_json::from_slice::<WeatherForecast>(&response.body)?);
 
-        result = Some(serde_json::from_str::<WeatherForecast>(string)?);
+        time = match (synced, response.date) {
+            (Some(source), _) => source,
+            (None, Some(date)) => {
+                clock::set_system_time(date)?;
+                TimeSource::Http
+            }
+            (None, None) => return Err(anyhow::Error::new(WeatherError::NoTime)),
+        };
     }
 
     #[cfg(target_os = "linux")]
@@ -547,10 +719,12 @@ async fn request_weather() -> anyhow::Result<WeatherForecast> {
         let json = res.unwrap().text().await.unwrap();
 
         result = Some(serde_json::from_str::<WeatherForecast>(&json)?);
+        network = None;
+        time = TimeSource::Ntp;
     }
 
     if let Some(result) = result {
-        Ok(result)
+        Ok((result, network, time))
     } else {
         Err(anyhow::Error::new(WeatherError::NoWeather))
     }
@@ -0,0 +1,174 @@
+use std::net::Ipv4Addr;
+
+use embedded_graphics::prelude::*;
+use epd_waveshare::color::TriColor;
+use u8g2_fonts::{
+    fonts::u8g2_font_helvR08_tf,
+    types::{FontColor, HorizontalAlignment, VerticalPosition},
+    FontRenderer,
+};
+
+use crate::secret::Secret;
+
+/// wifi the device may connect to
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnownNetwork {
+    pub ssid: String,
+    pub password: Secret,
+    /// networks with a higher priority are tried first, the signal strength decides between equal priorities
+    pub priority: u8,
+}
+
+impl KnownNetwork {
+    /// parse the `WIFI_NETWORKS` list like `office:secret:1;home:password`. Entries are separated by `;`,
+    /// the fields by `:`, the priority is optional.
+    pub fn list_from_code(code: &str) -> Vec<Self> {
+        code.split(';')
+            .filter_map(|entry| {
+                let mut fields = entry.split(':');
+                let ssid = fields.next()?.trim();
+                let password = fields.next().unwrap_or_default();
+                let priority = match fields.next() {
+                    Some(priority) => priority.trim().parse().ok()?,
+                    None => 0,
+                };
+                (!ssid.is_empty() && fields.next().is_none()).then(|| Self {
+                    ssid: ssid.to_string(),
+                    password: Secret::new(password),
+                    priority,
+                })
+            })
+            .collect()
+    }
+}
+
+/// access point found by the wifi scan
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanResult {
+    pub ssid: String,
+    pub signal: i8,
+    pub channel: u8,
+}
+
+/// a known network to try, with the channel and signal strength if it was found by the scan
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate<'a> {
+    pub network: &'a KnownNetwork,
+    pub channel: Option<u8>,
+    pub signal: Option<i8>,
+    /// connect to this access point without scanning
+    pub bssid: Option<[u8; 6]>,
+}
+
+/// order in which the known networks are tried. Networks found by the scan come first, by priority and then by signal strength.
+/// Networks missing in the scan might be hidden, so they are tried last without channel.
+pub fn connection_order<'a>(known: &'a [KnownNetwork], scan: &[ScanResult]) -> Vec<Candidate<'a>> {
+    let mut candidates = known
+        .iter()
+        .map(|network| {
+            let strongest = (scan.iter())
+                .filter(|result| result.ssid == network.ssid)
+                .max_by_key(|result| result.signal);
+            Candidate {
+                network,
+                channel: strongest.map(|result| result.channel),
+                signal: strongest.map(|result| result.signal),
+                bssid: None,
+            }
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|candidate| {
+        (
+            candidate.signal.is_none(),
+            std::cmp::Reverse(candidate.network.priority),
+            std::cmp::Reverse(candidate.signal),
+        )
+    });
+    candidates
+}
+
+/// ip configuration received by dhcp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lease {
+    pub ip: Ipv4Addr,
+    pub gateway: Ipv4Addr,
+    /// length of the subnet mask
+    pub prefix: u8,
+    pub dns: Option<Ipv4Addr>,
+}
+
+/// access point and lease of the last successful connection. It is plain data so it can be kept in rtc memory
+/// across the deep sleep, the next wake cycle connects directly without scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastConnection {
+    ssid: [u8; 32],
+    ssid_length: u8,
+    pub bssid: [u8; 6],
+    pub channel: u8,
+    pub lease: Option<Lease>,
+}
+
+impl LastConnection {
+    /// `None` if the ssid does not fit into 32 bytes
+    pub fn new(ssid: &str, bssid: [u8; 6], channel: u8, lease: Option<Lease>) -> Option<Self> {
+        let mut buffer = [0; 32];
+        buffer
+            .get_mut(..ssid.len())?
+            .copy_from_slice(ssid.as_bytes());
+        Some(Self {
+            ssid: buffer,
+            ssid_length: ssid.len() as u8,
+            bssid,
+            channel,
+            lease,
+        })
+    }
+
+    pub fn ssid(&self) -> &str {
+        std::str::from_utf8(&self.ssid[..self.ssid_length as usize]).unwrap_or_default()
+    }
+
+    /// the known network to reconnect to, as candidate for the cached access point.
+    /// `None` if the network was removed from the configuration in the meantime.
+    pub fn candidate<'a>(&self, known: &'a [KnownNetwork]) -> Option<Candidate<'a>> {
+        let network = known.iter().find(|network| network.ssid == self.ssid())?;
+        Some(Candidate {
+            network,
+            channel: Some(self.channel),
+            signal: None,
+            bssid: Some(self.bssid),
+        })
+    }
+}
+
+/// the network the device is connected to, shown in the status area of the display
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkStatus {
+    pub ssid: String,
+    pub signal: Option<i8>,
+    pub ip: Option<Ipv4Addr>,
+}
+
+impl NetworkStatus {
+    /// name and signal strength of the network, `point` is the left of the text
+    pub fn draw<Display>(&self, display: &mut Display, point: Point)
+    where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        let text = match self.signal {
+            Some(signal) => format!("{} ({} dBm)", self.ssid, signal),
+            None => self.ssid.clone(),
+        };
+        FontRenderer::new::<u8g2_font_helvR08_tf>()
+            .render_aligned(
+                text.as_str(),
+                point,
+                VerticalPosition::Center,
+                HorizontalAlignment::Left,
+                FontColor::Transparent(TriColor::Black),
+                display,
+            )
+            .unwrap();
+    }
+}
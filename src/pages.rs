@@ -0,0 +1,885 @@
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Timelike};
+use embedded_graphics::{
+    prelude::*,
+    primitives::{Circle, Line, PrimitiveStyle, Rectangle, RoundedRectangle, StyledDrawable},
+};
+use epd_waveshare::color::TriColor;
+use u8g2_fonts::{
+    fonts::{
+        u8g2_font_helvB08_tf, u8g2_font_helvB10_tf, u8g2_font_helvB24_tf, u8g2_font_helvR08_tf,
+    },
+    types::{FontColor, HorizontalAlignment, VerticalPosition},
+    FontRenderer,
+};
+
+use crate::{
+    chart::{peaks, AreaSeries, BarSeries, Chart, Dash, Legend, LineSeries, TimeAxis, ValueAxis},
+    constants::{DISPLAY_HEIGHT, DISPLAY_WIDTH},
+    layout::{Area, Layout},
+    locale::Language,
+    text::{text_width, TextBox},
+    units::{Factor, Temperature},
+    weather::{
+        air_quality::{AirQuality, PollenLevel},
+        WeatherDaily, WeatherForecast,
+    },
+};
+
+/// warning banner of the pages without a layout, right below the status strip
+const BANNER: Area = Area::new(0, 52, DISPLAY_WIDTH, 36);
+
+/// view of the forecast below the status strip, every page renders the same forecast
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Page {
+    /// the current conditions and the next 24 hours
+    Today,
+    /// graph of the next three days
+    #[default]
+    ThreeDays,
+    /// table of the next seven days
+    Week,
+}
+
+impl Page {
+    pub const ALL: [Self; 3] = [Self::Today, Self::ThreeDays, Self::Week];
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_lowercase().as_str() {
+            "today" => Some(Self::Today),
+            "three-days" | "3days" => Some(Self::ThreeDays),
+            "week" | "7days" => Some(Self::Week),
+            _ => None,
+        }
+    }
+
+    /// where the warning banner is drawn. The three day graph leaves the slot of the `layout` free,
+    /// the other pages move their content below the banner while there are `warnings`.
+    pub fn banner(&self, layout: &Layout) -> Area {
+        match self {
+            Self::ThreeDays => layout.warnings,
+            Self::Today | Self::Week => BANNER,
+        }
+    }
+
+    /// draw the page, `now` is the local time of the forecast. The three day graph is placed by the `layout`.
+    /// With `warnings` the content leaves room for the banner.
+    pub fn draw<Display>(
+        &self,
+        display: &mut Display,
+        weather: &WeatherForecast,
+        language: Language,
+        layout: &Layout,
+        now: NaiveDateTime,
+        warnings: bool,
+    ) where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        let offset = if warnings {
+            BANNER.height as i32 + 4
+        } else {
+            0
+        };
+        match self {
+            Self::Today => draw_today(display, weather, language, now, offset),
+            Self::ThreeDays => draw_three_days(display, weather, language, layout),
+            Self::Week => draw_week(display, weather, language, offset),
+        }
+    }
+}
+
+/// how the page of a full refresh is chosen. Selected with the `PAGES` environment variable:
+/// - `week` always shows the same page
+/// - `today,three-days,week` shows the next page of the list with every forecast
+/// - `06:00 today,12:00 three-days,20:00 week` shows the page of the last time of day before the refresh
+///
+/// A short press on the button shows the next page in any case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PageSelection {
+    Fixed(Page),
+    Rotate(Vec<Page>),
+    Schedule(Vec<(NaiveTime, Page)>),
+}
+
+impl Default for PageSelection {
+    fn default() -> Self {
+        Self::Fixed(Page::default())
+    }
+}
+
+impl PageSelection {
+    pub fn from_code(code: &str) -> Option<Self> {
+        let entries = code.split(',').map(str::trim).collect::<Vec<_>>();
+        if entries.iter().all(|entry| entry.contains(' ')) {
+            let schedule = (entries.iter())
+                .map(|entry| {
+                    let (time, page) = entry.split_once(' ')?;
+                    Some((
+                        NaiveTime::parse_from_str(time, "%H:%M").ok()?,
+                        Page::from_code(page)?,
+                    ))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            return Some(Self::Schedule(schedule));
+        }
+        let pages = (entries.into_iter())
+            .map(Page::from_code)
+            .collect::<Option<Vec<_>>>()?;
+        match pages.as_slice() {
+            [page] => Some(Self::Fixed(*page)),
+            _ => Some(Self::Rotate(pages)),
+        }
+    }
+
+    /// the pages the button cycles through
+    pub fn pages(&self) -> Vec<Page> {
+        match self {
+            Self::Rotate(pages) => pages.clone(),
+            Self::Fixed(_) | Self::Schedule(_) => Page::ALL.to_vec(),
+        }
+    }
+
+    /// page of the next full refresh. `last` is the page shown before the deep sleep, `next_page` is set when the
+    /// button asked for the next page and `time` is the local time.
+    pub fn select(&self, last: Option<Page>, next_page: bool, time: NaiveTime) -> Page {
+        let following = |last: Page| {
+            let pages = self.pages();
+            pages
+                .iter()
+                .position(|page| *page == last)
+                .map_or(pages[0], |i| pages[(i + 1) % pages.len()])
+        };
+        match (self, last) {
+            (_, Some(last)) if next_page => following(last),
+            (Self::Fixed(page), _) => *page,
+            (Self::Rotate(_), Some(last)) => following(last),
+            (Self::Rotate(pages), None) => pages[0],
+            // before the first time of the day the last one of the day before still applies
+            (Self::Schedule(schedule), _) => (schedule.iter())
+                .filter(|(start, _)| *start <= time)
+                .max_by_key(|(start, _)| *start)
+                .or_else(|| schedule.iter().max_by_key(|(start, _)| *start))
+                .map(|(_, page)| *page)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// the current conditions and an hourly graph of the next 24 hours, moved down by `offset` pixels
+fn draw_today<Display>(
+    display: &mut Display,
+    weather: &WeatherForecast,
+    language: Language,
+    now: NaiveDateTime,
+    offset: i32,
+) where
+    Display: DrawTarget<Color = TriColor>,
+    Display::Error: std::fmt::Debug,
+{
+    const HOURS: usize = 24;
+    let height = DISPLAY_HEIGHT as i32;
+    let width = DISPLAY_WIDTH as i32;
+    let font = FontRenderer::new::<u8g2_font_helvR08_tf>();
+    let text = |display: &mut Display, text: &str, point: Point, alignment, color| {
+        font.render_aligned(
+            text,
+            point,
+            VerticalPosition::Baseline,
+            alignment,
+            FontColor::Transparent(color),
+            display,
+        )
+        .unwrap();
+    };
+
+    let hourly = &weather.hourly;
+    let start = (hourly.time.iter())
+        .position(|time| time.0.date() == now.date() && time.0.hour() == now.hour())
+        .unwrap_or_default();
+    let end = (start + HOURS).min(hourly.time.len());
+    if start >= end {
+        return;
+    }
+    let is_day = |time: NaiveDateTime| {
+        (weather.daily.time.iter())
+            .zip(weather.daily.sunrise.iter().zip(&weather.daily.sunset))
+            .find(|(date, _)| date.0 == time.date())
+            .is_none_or(|(_, (sunrise, sunset))| time >= sunrise.0 && time <= sunset.0)
+    };
+
+    // current conditions
+    let current = hourly.weather_code[start];
+    current.draw_icon(
+        display,
+        width / 6,
+        60 + offset,
+        hourly.cloud_cover[start],
+        is_day(hourly.time[start].0),
+    );
+    FontRenderer::new::<u8g2_font_helvB24_tf>()
+        .render_aligned(
+            hourly.temperature_2m[start].to_string().as_str(),
+            Point::new(width / 3, 95 + offset),
+            VerticalPosition::Baseline,
+            HorizontalAlignment::Center,
+            FontColor::Transparent(TriColor::Black),
+            display,
+        )
+        .unwrap();
+    TextBox {
+        area: Rectangle::new(
+            Point::new(width / 6 + 10, 108 + offset),
+            Size::new(width as u32 / 3 - 20, 30),
+        ),
+        fonts: &[
+            FontRenderer::new::<u8g2_font_helvB10_tf>(),
+            FontRenderer::new::<u8g2_font_helvR08_tf>(),
+        ],
+        alignment: HorizontalAlignment::Center,
+        middle: false,
+        color: TriColor::Black,
+    }
+    .draw(display, current.description(language));
+
+    let daily = &weather.daily;
+    if let Some(today) = daily.time.iter().position(|date| date.0 == now.date()) {
+        text(
+            display,
+            &format!(
+                "{:#} / {:#}",
+                daily.temperature_2m_min[today], daily.temperature_2m_max[today]
+            ),
+            Point::new(width * 2 / 3, 80 + offset),
+            HorizontalAlignment::Left,
+            TriColor::Black,
+        );
+        text(
+            display,
+            &language.localize_number(&daily.precipitation_sum[today].to_string()),
+            Point::new(width * 2 / 3, 100 + offset),
+            HorizontalAlignment::Left,
+            TriColor::Chromatic,
+        );
+    }
+    text(
+        display,
+        &hourly.wind_speed_10m[start].to_string(),
+        Point::new(width * 2 / 3, 120 + offset),
+        HorizontalAlignment::Left,
+        TriColor::Black,
+    );
+
+    // hourly graph, the temperature is scaled to the range of the day
+    let column = width / HOURS as i32;
+    // on small panels the graph keeps a minimum height below the banner
+    let graph_bottom = height - 95;
+    let graph_top = (165 + offset).min(graph_bottom - 24);
+    let rain_bottom = height - 50;
+    let temperatures = &hourly.temperature_2m[start..end];
+    let min = (temperatures.iter()).fold(f32::MAX, |min, temp| min.min(temp.celsius()));
+    let max = (temperatures.iter()).fold(f32::MIN, |max, temp| max.max(temp.celsius()));
+    let chart = Chart {
+        area: Rectangle::with_corners(
+            Point::new(column / 2, graph_top),
+            Point::new(width - 1, graph_bottom),
+        ),
+        step: column as u32,
+        min,
+        max: max.max(min + 1.0),
+    };
+    LineSeries::new(TriColor::Black, 2).draw(
+        display,
+        &chart,
+        temperatures.iter().map(|temp| temp.celsius()),
+    );
+
+    // 10 pixels per millimeter up to 3 mm, the probability is filled behind the bars with the same height
+    let rain = Chart {
+        area: Rectangle::with_corners(
+            Point::new(column / 2, rain_bottom - 30),
+            Point::new(width - 1, rain_bottom),
+        ),
+        min: 0.0,
+        max: 3.0,
+        ..chart
+    };
+    AreaSeries {
+        color: TriColor::Chromatic,
+    }
+    .draw(
+        display,
+        &Chart { max: 100.0, ..rain },
+        (hourly.precipitation_probability[start..end].iter())
+            .map(|probability| probability.value()),
+    );
+    BarSeries {
+        color: TriColor::Black,
+        width: column as u32 / 2,
+    }
+    .draw(
+        display,
+        &rain,
+        (hourly.precipitation[start..end].iter())
+            .map(|precipitation| precipitation.millimeter().min(rain.max)),
+    );
+    // the bars stand on the time axis, the icons are below the hours
+    TimeAxis {
+        color: TriColor::Black,
+        tick: 3,
+    }
+    .draw(
+        display,
+        &rain,
+        (start..end)
+            .step_by(2)
+            .map(|i| (i - start, hourly.time[i].0.hour().to_string())),
+    );
+
+    for i in (start..end).step_by(3) {
+        let temp = hourly.temperature_2m[i];
+        text(
+            display,
+            &format!("{:#}", temp),
+            chart.point(i - start, temp.celsius()) - Point::new(0, 8),
+            HorizontalAlignment::Center,
+            TriColor::Black,
+        );
+        hourly.weather_code[i].draw_icon(
+            display,
+            chart.x(i - start),
+            height - 35,
+            hourly.cloud_cover[i],
+            is_day(hourly.time[i].0),
+        );
+    }
+}
+
+/// table of the next seven days with the condition, the temperature range and the precipitation, moved down by `offset` pixels
+fn draw_week<Display>(
+    display: &mut Display,
+    weather: &WeatherForecast,
+    language: Language,
+    offset: i32,
+) where
+    Display: DrawTarget<Color = TriColor>,
+    Display::Error: std::fmt::Debug,
+{
+    const DAYS: i32 = 7;
+    // the icons have to fit into a row, below a banner small panels show fewer days
+    const MIN_ROW: i32 = 34;
+    let width = DISPLAY_WIDTH as i32;
+    let table = 55 + offset;
+    let days = DAYS.min((DISPLAY_HEIGHT as i32 - table) / MIN_ROW).max(1);
+    let row = (DISPLAY_HEIGHT as i32 - table) / days;
+    let font = FontRenderer::new::<u8g2_font_helvR08_tf>();
+
+    let daily = &weather.daily;
+    for (i, day) in daily.time.iter().take(days as usize).enumerate() {
+        let top = table + i as i32 * row;
+        let baseline = top + row / 2 + 5;
+        if i > 0 {
+            Line::new(Point::new(10, top), Point::new(width - 10, top))
+                .draw_styled(&PrimitiveStyle::with_stroke(TriColor::Black, 1), display)
+                .unwrap();
+        }
+
+        TextBox {
+            area: Rectangle::new(
+                Point::new(10, top),
+                Size::new(width as u32 / 4 - 20, row as u32),
+            ),
+            fonts: &[
+                FontRenderer::new::<u8g2_font_helvB10_tf>(),
+                FontRenderer::new::<u8g2_font_helvR08_tf>(),
+            ],
+            alignment: HorizontalAlignment::Left,
+            middle: true,
+            color: TriColor::Black,
+        }
+        .draw(display, language.weekday(day.0.weekday()));
+        font.render_aligned(
+            language.format_date(day.0).as_str(),
+            Point::new(width / 4, baseline),
+            VerticalPosition::Baseline,
+            HorizontalAlignment::Left,
+            FontColor::Transparent(TriColor::Black),
+            display,
+        )
+        .unwrap();
+
+        // the daily values have no cloud cover, the day icon is used
+        if let Some(code) = daily.weather_code.get(i) {
+            code.draw_icon(
+                display,
+                width / 2,
+                top + (row - 32) / 2,
+                Factor::default(),
+                true,
+            );
+        }
+
+        if let (Some(min), Some(max)) = (
+            daily.temperature_2m_min.get(i),
+            daily.temperature_2m_max.get(i),
+        ) {
+            font.render_aligned(
+                format!("{:#} / {:#}", min, max).as_str(),
+                Point::new(width * 2 / 3, baseline),
+                VerticalPosition::Baseline,
+                HorizontalAlignment::Center,
+                FontColor::Transparent(TriColor::Black),
+                display,
+            )
+            .unwrap();
+        }
+
+        if let Some(precipitation) = daily.precipitation_sum.get(i) {
+            let color = if precipitation.value() > 0.0 {
+                TriColor::Chromatic
+            } else {
+                TriColor::Black
+            };
+            font.render_aligned(
+                language
+                    .localize_number(&precipitation.to_string())
+                    .as_str(),
+                Point::new(width - 10, baseline),
+                VerticalPosition::Baseline,
+                HorizontalAlignment::Right,
+                FontColor::Transparent(color),
+                display,
+            )
+            .unwrap();
+        }
+    }
+}
+
+/// the condition and wind of each day over a graph of the temperature, the rain and the sun of the next three days
+fn draw_three_days<Display>(
+    display: &mut Display,
+    weather: &WeatherForecast,
+    language: Language,
+    layout: &Layout,
+) where
+    Display: DrawTarget<Color = TriColor>,
+    Display::Error: std::fmt::Debug,
+{
+    const DAYS: usize = 3;
+    const HOURS: usize = DAYS * 24;
+
+    let header = layout.header;
+    let column = (header.width / DAYS as u32) as i32;
+    let bold = [
+        FontRenderer::new::<u8g2_font_helvB10_tf>(),
+        FontRenderer::new::<u8g2_font_helvR08_tf>(),
+    ];
+    let regular = [FontRenderer::new::<u8g2_font_helvR08_tf>()];
+    for (i, day) in weather.daily.time.iter().take(DAYS).enumerate() {
+        let x = header.x as i32 + (column * (i as i32)) + column / 2;
+        let y = header.y as i32;
+        // long names get a smaller font or are shortened to the column
+        let column_box = |top: i32, height: u32, fonts| TextBox {
+            area: Rectangle::new(
+                Point::new(x - column / 2 + 2, top),
+                Size::new(column as u32 - 4, height),
+            ),
+            fonts,
+            alignment: HorizontalAlignment::Center,
+            middle: false,
+            color: TriColor::Black,
+        };
+        column_box(y - 1, 14, &bold).draw(display, language.weekday(day.0.weekday()));
+
+        FontRenderer::new::<u8g2_font_helvR08_tf>()
+            .render_aligned(
+                language.format_date(day.0).as_str(),
+                Point::new(x, y + 25),
+                VerticalPosition::Baseline,
+                HorizontalAlignment::Center,
+                FontColor::Transparent(TriColor::Black),
+                display,
+            )
+            .unwrap();
+
+        // the most severe condition of the day
+        if let Some(code) = weather
+            .hourly
+            .time
+            .iter()
+            .zip(&weather.hourly.weather_code)
+            .filter(|(time, _)| time.0.date() == day.0)
+            .map(|(_, code)| *code)
+            .max()
+        {
+            column_box(y + 32, 11, &regular).draw(display, code.description(language));
+        }
+
+        // the strongest wind of the day
+        if let Some(wind_speed) = weather
+            .hourly
+            .time
+            .iter()
+            .zip(&weather.hourly.wind_speed_10m)
+            .filter(|(time, _)| time.0.date() == day.0)
+            .map(|(_, wind_speed)| *wind_speed)
+            .reduce(|a, b| if b.value() > a.value() { b } else { a })
+        {
+            FontRenderer::new::<u8g2_font_helvR08_tf>()
+                .render_aligned(
+                    wind_speed.to_string().as_str(),
+                    Point::new(x, y + 55),
+                    VerticalPosition::Baseline,
+                    HorizontalAlignment::Center,
+                    FontColor::Transparent(TriColor::Black),
+                    display,
+                )
+                .unwrap();
+        }
+
+        // hours of sunshine behind a sun left and the uv badge right of the wind
+        if let Some(hours) = weather.daily.sunshine_hours(i) {
+            let left = x - column / 2 + 6;
+            Circle::new(Point::new(left, y + 48), 7)
+                .into_styled(PrimitiveStyle::with_fill(TriColor::Chromatic))
+                .draw(display)
+                .unwrap();
+            FontRenderer::new::<u8g2_font_helvR08_tf>()
+                .render_aligned(
+                    format!("{} h", language.format_decimal(hours, 1)).as_str(),
+                    Point::new(left + 10, y + 55),
+                    VerticalPosition::Baseline,
+                    HorizontalAlignment::Left,
+                    FontColor::Transparent(TriColor::Black),
+                    display,
+                )
+                .unwrap();
+        }
+        if let Some(uv_index) = weather.daily.uv_index(i) {
+            draw_uv_badge(display, uv_index, Point::new(x + column / 2 - 6, y + 55));
+        }
+    }
+
+    if !weather.hourly.air_quality.is_empty() {
+        draw_air_quality(display, weather, language, &layout.air, DAYS);
+    }
+
+    let units = weather.hourly_units;
+
+    let font = FontRenderer::new::<u8g2_font_helvR08_tf>();
+
+    // the legends are left of the lanes
+    let graph = layout.temperature;
+    Legend {
+        entries: &[(units.temperature_2m.symbol(), TriColor::Black)],
+    }
+    .draw(display, Point::new(0, graph.area.y as i32));
+
+    // scaled in celsius, so the graph keeps its height in every unit
+    let chart = Chart {
+        area: graph.area.rectangle(),
+        step: layout.hour_width,
+        min: graph.min,
+        max: graph.max,
+    };
+    // the freezing point as dashed gridline
+    let freezing = Temperature::new(units.temperature_2m.from_celsius(0.0), units.temperature_2m);
+    ValueAxis {
+        color: TriColor::Black,
+        tick: 2,
+        grid: Some(Dash { on: 2, off: 3 }),
+    }
+    .draw(
+        display,
+        &chart,
+        (chart.min < 0.0 && chart.max > 0.0).then(|| (0.0, format!("{:#}", freezing))),
+    );
+    let temperatures = weather.hourly.temperature_2m.iter().take(HOURS);
+    LineSeries::new(TriColor::Black, 1).draw(
+        display,
+        &chart,
+        temperatures.clone().map(|temp| temp.celsius()),
+    );
+    for (i, temp) in temperatures
+        .enumerate()
+        .take(chart.samples())
+        .step_by(graph.labels)
+    {
+        font.render_aligned(
+            format!("{:#}", temp).as_str(),
+            chart.point(i, temp.celsius()) - Point::new(0, 10),
+            VerticalPosition::Center,
+            HorizontalAlignment::Center,
+            FontColor::Transparent(TriColor::Black),
+            display,
+        )
+        .unwrap();
+    }
+
+    let rain = layout.rain;
+    Legend {
+        entries: &[("%", TriColor::Chromatic)],
+    }
+    .draw(display, Point::new(0, rain.area.y as i32));
+    Legend {
+        entries: &[(units.precipitation.symbol(), TriColor::Black)],
+    }
+    .draw(display, Point::new(0, rain.area.y as i32 + 14));
+    // the bars are scaled with `scale`, the probability uses the whole height
+    let chart = Chart {
+        area: rain.area.rectangle(),
+        step: layout.hour_width,
+        min: 0.0,
+        max: rain.area.height.saturating_sub(1).max(1) as f32 / rain.scale,
+    };
+    let precipitation = (weather.hourly.precipitation.iter())
+        .take(HOURS.min(chart.samples()))
+        .collect::<Vec<_>>();
+    BarSeries {
+        color: TriColor::Black,
+        width: layout.hour_width,
+    }
+    .draw(
+        display,
+        &chart,
+        (precipitation.iter()).map(|precipitation| precipitation.millimeter()),
+    );
+    LineSeries::new(TriColor::Chromatic, 1).draw(
+        display,
+        &Chart {
+            min: 0.0,
+            max: 100.0,
+            ..chart
+        },
+        (weather.hourly.precipitation_probability.iter())
+            .take(HOURS)
+            .map(|probability| probability.value()),
+    );
+
+    // the amount above the heaviest hour of each shower
+    let amounts = (precipitation.iter()).map(|precipitation| precipitation.millimeter());
+    for i in peaks(amounts, chart.min) {
+        font.render_aligned(
+            language
+                .localize_number(&format!("{:#}", precipitation[i]))
+                .as_str(),
+            chart.point(i, precipitation[i].millimeter()) - Point::new(0, 10),
+            VerticalPosition::Center,
+            HorizontalAlignment::Center,
+            FontColor::Transparent(TriColor::Black),
+            display,
+        )
+        .unwrap();
+    }
+
+    let sun = layout.sun;
+    let hours = HOURS.min(layout.hours_in(&sun)) as u32;
+    for (i, (sunrise, sunset)) in (weather.daily.sunrise)
+        .iter()
+        .zip(&(weather.daily).sunset)
+        .take(DAYS)
+        .enumerate()
+    {
+        // sunrise and sunset, in pixels from the start of the lane
+        let offset = |time: NaiveDateTime| {
+            let hour = (i as u32 * 24 + time.hour()).min(hours);
+            (hour * layout.hour_width + time.minute() * layout.hour_width / 60) as i32
+        };
+
+        let sunrise_x = sun.x as i32 + offset(sunrise.0);
+        let sunset_x = sun.x as i32 + offset(sunset.0);
+
+        // the daylight as thin line, the sunshine as bar of the same share around noon
+        let ratio = weather.daily.sunshine_ratio(i).unwrap_or(1.0);
+        let length = ((sunset_x - sunrise_x) as f32 * ratio).round() as i32;
+        let noon = (sunrise_x + sunset_x) / 2;
+        Line::new(
+            Point::new(sunrise_x, sun.center_y()),
+            Point::new(sunset_x, sun.center_y()),
+        )
+        .draw_styled(&PrimitiveStyle::with_stroke(TriColor::Black, 1), display)
+        .unwrap();
+        if length > 0 {
+            Line::new(
+                Point::new(noon - length / 2, sun.center_y()),
+                Point::new(noon - length / 2 + length, sun.center_y()),
+            )
+            .draw_styled(
+                &PrimitiveStyle::with_stroke(TriColor::Chromatic, sun.height),
+                display,
+            )
+            .unwrap();
+        }
+    }
+
+    // the axis runs along the top of the hour row, the hours are below it
+    let row = layout.hours;
+    TimeAxis {
+        color: TriColor::Black,
+        tick: 1,
+    }
+    .draw(
+        display,
+        &Chart {
+            area: Rectangle::new(
+                Point::new(row.area.x as i32, row.area.y as i32 - 1),
+                Size::new(row.area.width, 1),
+            ),
+            step: layout.hour_width,
+            min: 0.0,
+            max: 1.0,
+        },
+        (weather.hourly.time.iter())
+            .take(HOURS.min(layout.hours_in(&row.area)))
+            .enumerate()
+            .step_by(row.every)
+            .map(|(i, time)| (i, time.0.hour().to_string())),
+    );
+
+    let row = layout.icons;
+    for (i, ((code, time), cloud_coverage)) in weather
+        .hourly
+        .weather_code
+        .iter()
+        .zip(&weather.hourly.time)
+        .zip(&weather.hourly.cloud_cover)
+        .take(HOURS.min(layout.hours_in(&row.area)))
+        .enumerate()
+    {
+        if i % row.every == 0 {
+            if let Some(((_date, sunrise), sunset)) = weather
+                .daily
+                .time
+                .iter()
+                .zip(&weather.daily.sunrise)
+                .zip(&weather.daily.sunset)
+                .find(|((date, _), _)| time.0.date().eq(&date.0))
+            {
+                let is_day = time.0 >= sunrise.0 && time.0 <= sunset.0;
+                code.draw_icon(
+                    display,
+                    layout.hour_x(&row.area, i),
+                    row.area.y as i32,
+                    *cloud_coverage,
+                    is_day,
+                );
+            }
+        }
+    }
+}
+
+/// the rounded `uv_index` of [`WeatherDaily::uv_index`] in a badge, red from [`WeatherDaily::UV_HIGH`].
+/// `right` is the right end of the baseline.
+fn draw_uv_badge<Display>(display: &mut Display, uv_index: f32, right: Point)
+where
+    Display: DrawTarget<Color = TriColor>,
+    Display::Error: std::fmt::Debug,
+{
+    let font = FontRenderer::new::<u8g2_font_helvB08_tf>();
+    let text = format!("UV {uv_index:.0}");
+    let width = text_width(&font, &text) as i32;
+    let high = uv_index >= WeatherDaily::UV_HIGH;
+    let badge = RoundedRectangle::with_equal_corners(
+        Rectangle::new(
+            right - Point::new(width + 6, 10),
+            Size::new(width as u32 + 6, 13),
+        ),
+        Size::new_equal(3),
+    );
+    let style = if high {
+        PrimitiveStyle::with_fill(TriColor::Chromatic)
+    } else {
+        PrimitiveStyle::with_stroke(TriColor::Black, 1)
+    };
+    badge.into_styled(style).draw(display).unwrap();
+    font.render_aligned(
+        text.as_str(),
+        right - Point::new(3, 0),
+        VerticalPosition::Baseline,
+        HorizontalAlignment::Right,
+        FontColor::Transparent(if high {
+            TriColor::White
+        } else {
+            TriColor::Black
+        }),
+        display,
+    )
+    .unwrap();
+}
+
+/// one badge per day with the highest european air quality index, fine particles and pollen of the day.
+/// Poor air and high pollen are red.
+fn draw_air_quality<Display>(
+    display: &mut Display,
+    weather: &WeatherForecast,
+    language: Language,
+    area: &Area,
+    days: usize,
+) where
+    Display: DrawTarget<Color = TriColor>,
+    Display::Error: std::fmt::Debug,
+{
+    let column = (area.width / days as u32) as i32;
+    let font = [FontRenderer::new::<u8g2_font_helvR08_tf>()];
+    for (i, day) in weather.daily.time.iter().take(days).enumerate() {
+        let Some(air) = AirQuality::daily_max(&weather.hourly, day.0) else {
+            continue;
+        };
+        let badge = Rectangle::new(
+            Point::new(area.x as i32 + column * i as i32 + 4, area.y as i32),
+            Size::new(column as u32 - 8, area.height),
+        );
+        badge
+            .into_styled(PrimitiveStyle::with_stroke(TriColor::Black, 1))
+            .draw(display)
+            .unwrap();
+
+        let pollen = air.pollen();
+        let particles = [
+            air.european_aqi.map(|aqi| format!("AQI {aqi:.0}")),
+            air.pm2_5.map(|pm2_5| format!("PM2.5 {pm2_5:.0} µg/m³")),
+        ];
+        let pollen_text = if pollen.is_empty() {
+            language.no_pollen().to_string()
+        } else {
+            (pollen.iter())
+                .map(|(pollen, level)| {
+                    format!("{} {}", pollen.name(language), level.name(language))
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let lines = [
+            (
+                particles
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join("  "),
+                air.is_poor(),
+            ),
+            (
+                pollen_text,
+                (pollen.iter()).any(|(_, level)| *level == PollenLevel::High),
+            ),
+        ];
+        let inner = badge.offset(-2);
+        let line_height = inner.size.height / 2;
+        for (line, (text, alarming)) in lines.iter().enumerate() {
+            TextBox {
+                area: Rectangle::new(
+                    inner.top_left + Point::new(0, (line_height * line as u32) as i32),
+                    Size::new(inner.size.width, line_height),
+                ),
+                fonts: &font,
+                alignment: HorizontalAlignment::Center,
+                middle: true,
+                color: if *alarming {
+                    TriColor::Chromatic
+                } else {
+                    TriColor::Black
+                },
+            }
+            .draw(display, text);
+        }
+    }
+}
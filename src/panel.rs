@@ -0,0 +1,132 @@
+//! the Waveshare panel, selected with exactly one of the cargo features
+//! `panel-7in5b-v2` (default), `panel-7in5b-v3`, `panel-7in5-v2` or `panel-4in2`.
+//! Everything is drawn in `TriColor`, the `Canvas` maps it to the colors of the panel.
+
+use embedded_graphics::{prelude::*, Pixel};
+use epd_waveshare::color::TriColor;
+
+#[cfg(feature = "panel-7in5b-v2")]
+pub use epd_waveshare::epd7in5b_v2::{Display7in5 as Display, Epd7in5 as Epd, HEIGHT, WIDTH};
+#[cfg(feature = "panel-7in5b-v3")]
+pub use epd_waveshare::epd7in5b_v3::{Display7in5 as Display, Epd7in5 as Epd, HEIGHT, WIDTH};
+#[cfg(feature = "panel-7in5-v2")]
+pub use epd_waveshare::epd7in5_v2::{Display7in5 as Display, Epd7in5 as Epd, HEIGHT, WIDTH};
+// epd-waveshare has no driver for the red plane of the 4.2 inch B panel, it is driven black and white
+#[cfg(feature = "panel-4in2")]
+pub use epd_waveshare::epd4in2::{Display4in2 as Display, Epd4in2 as Epd, HEIGHT, WIDTH};
+
+#[cfg(not(any(
+    feature = "panel-7in5b-v2",
+    feature = "panel-7in5b-v3",
+    feature = "panel-7in5-v2",
+    feature = "panel-4in2"
+)))]
+compile_error!("select a panel with one of the features panel-7in5b-v2, panel-7in5b-v3, panel-7in5-v2 or panel-4in2");
+
+#[cfg(any(
+    all(feature = "panel-7in5b-v2", feature = "panel-7in5b-v3"),
+    all(feature = "panel-7in5b-v2", feature = "panel-7in5-v2"),
+    all(feature = "panel-7in5b-v2", feature = "panel-4in2"),
+    all(feature = "panel-7in5b-v3", feature = "panel-7in5-v2"),
+    all(feature = "panel-7in5b-v3", feature = "panel-4in2"),
+    all(feature = "panel-7in5-v2", feature = "panel-4in2")
+))]
+compile_error!("select only one panel feature, the default panel-7in5b-v2 has to be disabled with --no-default-features");
+
+/// whether the panel has a red plane. Panels without it need a single color plane and draw red as black or dithered.
+pub const TRI_COLOR: bool = cfg!(any(feature = "panel-7in5b-v2", feature = "panel-7in5b-v3"));
+
+/// whether the panel can refresh a region. The 4.2 inch panel uses the partial frame of the driver and the
+/// red panels `PartialUpdate`, the driver of the black and white 7.5 inch panel has none.
+pub const PARTIAL: bool = cfg!(any(
+    feature = "panel-7in5b-v2",
+    feature = "panel-7in5b-v3",
+    feature = "panel-4in2"
+));
+
+/// how red is drawn on black and white panels. Selected with the `MONO_RED` environment variable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MonoRed {
+    Black,
+    /// every second pixel, so red areas appear gray and stay distinguishable from black
+    #[default]
+    Dither,
+}
+
+impl MonoRed {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_lowercase().as_str() {
+            "black" => Some(Self::Black),
+            "dither" => Some(Self::Dither),
+            _ => None,
+        }
+    }
+
+    /// black or white for a pixel of a black and white panel
+    pub fn is_black(&self, color: TriColor, point: Point) -> bool {
+        match color {
+            TriColor::White => false,
+            TriColor::Black => true,
+            TriColor::Chromatic => match self {
+                Self::Black => true,
+                Self::Dither => (point.x + point.y) % 2 == 0,
+            },
+        }
+    }
+}
+
+/// frame buffer of the selected panel, drawn in `TriColor`
+pub struct Canvas {
+    display: Display,
+    red: MonoRed,
+}
+
+impl Canvas {
+    pub fn new(red: MonoRed) -> Self {
+        Self {
+            display: Display::default(),
+            red,
+        }
+    }
+
+    /// the packed color planes sent to the panel
+    pub fn buffer(&self) -> &[u8] {
+        self.display.buffer()
+    }
+
+    #[cfg(any(feature = "panel-7in5b-v2", feature = "panel-7in5b-v3"))]
+    fn native(_red: MonoRed, color: TriColor, _point: Point) -> TriColor {
+        color
+    }
+
+    #[cfg(any(feature = "panel-7in5-v2", feature = "panel-4in2"))]
+    fn native(red: MonoRed, color: TriColor, point: Point) -> epd_waveshare::color::Color {
+        if red.is_black(color, point) {
+            epd_waveshare::color::Color::Black
+        } else {
+            epd_waveshare::color::Color::White
+        }
+    }
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        self.display.size()
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = TriColor;
+    type Error = <Display as DrawTarget>::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let red = self.red;
+        self.display.draw_iter(
+            (pixels.into_iter())
+                .map(|Pixel(point, color)| Pixel(point, Self::native(red, color, point))),
+        )
+    }
+}
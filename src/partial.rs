@@ -0,0 +1,93 @@
+use std::time::{Duration, Instant};
+
+use esp_idf_svc::hal::{
+    delay::FreeRtos,
+    gpio::{AnyIOPin, AnyInputPin, Input, InputOutput, PinDriver},
+    spi::{SpiDeviceDriver, SpiDriver},
+};
+
+use crate::framebuffer::Region;
+
+/// commands of the UC8179 controller of the 7.5 inch panels which are not exposed by `epd-waveshare`
+mod command {
+    pub const POWER_OFF: u8 = 0x02;
+    pub const DEEP_SLEEP: u8 = 0x07;
+    pub const DATA_START_TRANSMISSION_1: u8 = 0x10;
+    pub const DISPLAY_REFRESH: u8 = 0x12;
+    pub const GET_STATUS: u8 = 0x71;
+    pub const PARTIAL_WINDOW: u8 = 0x90;
+    pub const PARTIAL_IN: u8 = 0x91;
+    pub const PARTIAL_OUT: u8 = 0x92;
+}
+
+/// a refresh of the whole panel takes about 16 seconds
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// the panel after `Epd::new`, which resets and initializes the controller
+pub struct PartialUpdate<'a> {
+    pub spi: &'a mut SpiDeviceDriver<'static, SpiDriver<'static>>,
+    pub busy: &'a mut PinDriver<'static, AnyInputPin, Input>,
+    pub dc: &'a mut PinDriver<'static, AnyIOPin, InputOutput>,
+}
+
+impl PartialUpdate<'_> {
+    /// refresh only `region` with the black plane cut out of the frame buffer, then put the panel to sleep.
+    /// The plane is sent with the same command as by `update_and_display_frame`, so it keeps the encoding of the buffer.
+    /// The chromatic plane is not sent, the controller keeps the red of the last full refresh.
+    pub fn update_and_sleep(&mut self, region: &Region, black: &[u8]) -> anyhow::Result<()> {
+        let x_end = region.x + region.width - 1;
+        let y_end = region.y + region.height - 1;
+
+        self.command(command::PARTIAL_IN, &[])?;
+        self.command(
+            command::PARTIAL_WINDOW,
+            &[
+                (region.x >> 8) as u8,
+                region.x as u8,
+                (x_end >> 8) as u8,
+                x_end as u8,
+                (region.y >> 8) as u8,
+                region.y as u8,
+                (y_end >> 8) as u8,
+                y_end as u8,
+                // only refresh inside the window
+                0x01,
+            ],
+        )?;
+        self.command(command::DATA_START_TRANSMISSION_1, black)?;
+        self.command(command::DISPLAY_REFRESH, &[])?;
+        FreeRtos::delay_ms(100);
+        self.wait_until_idle()?;
+        self.command(command::PARTIAL_OUT, &[])?;
+
+        self.command(command::POWER_OFF, &[])?;
+        self.wait_until_idle()?;
+        self.command(command::DEEP_SLEEP, &[0xa5])?;
+        Ok(())
+    }
+
+    fn command(&mut self, command: u8, data: &[u8]) -> anyhow::Result<()> {
+        self.dc.set_low()?;
+        self.spi.write(&[command])?;
+        if !data.is_empty() {
+            self.dc.set_high()?;
+            self.spi.write(data)?;
+        }
+        Ok(())
+    }
+
+    /// the busy pin is low while the controller works
+    fn wait_until_idle(&mut self) -> anyhow::Result<()> {
+        let deadline = Instant::now() + BUSY_TIMEOUT;
+        loop {
+            self.command(command::GET_STATUS, &[])?;
+            if self.busy.is_high() {
+                return Ok(());
+            }
+            if Instant::now() > deadline {
+                anyhow::bail!("panel still busy after {} seconds", BUSY_TIMEOUT.as_secs());
+            }
+            FreeRtos::delay_ms(10);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    io::ErrorKind,
+    net::UdpSocket,
+    time::{Duration, Instant},
+};
+
+use embedded_svc::io::{Read, Write};
+use esp_idf_svc::{
+    eventloop::EspSystemEventLoop,
+    hal::modem::WifiModem,
+    http::{
+        server::{Configuration as HttpConfiguration, EspHttpServer},
+        Method,
+    },
+    nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault},
+    wifi::{BlockingWifi, Configuration, EspWifi},
+};
+
+use crate::{
+    config::AccessPoint,
+    provisioning::{dns_response, form_page, saved_page, Settings},
+    secret::Secret,
+    weather::Location,
+    wifi::access_point_configuration,
+};
+
+const NAMESPACE: &str = "weather";
+
+/// settings saved by the provisioning form, `None` if the device was never provisioned
+pub fn load(partition: &EspDefaultNvsPartition) -> anyhow::Result<Option<Settings>> {
+    let nvs = EspNvs::<NvsDefault>::new(partition.clone(), NAMESPACE, true)?;
+    let mut buffer = [0u8; 128];
+
+    let Some(ssid) = nvs.get_str("ssid", &mut buffer)?.map(str::to_string) else {
+        return Ok(None);
+    };
+    let password = Secret::new((nvs.get_str("password", &mut buffer)?).unwrap_or_default());
+    let location = (nvs.get_str("location", &mut buffer)?)
+        .and_then(Location::from_code)
+        .unwrap_or_default();
+    let refresh = nvs.get_u32("refresh")?.unwrap_or(60);
+
+    Ok(Some(Settings {
+        ssid,
+        password,
+        location,
+        refresh,
+    }))
+}
+
+pub fn save(partition: &EspDefaultNvsPartition, settings: &Settings) -> anyhow::Result<()> {
+    let mut nvs = EspNvs::<NvsDefault>::new(partition.clone(), NAMESPACE, true)?;
+    nvs.set_str("ssid", &settings.ssid)?;
+    nvs.set_str("password", settings.password.expose())?;
+    nvs.set_str("location", &settings.location.to_string())?;
+    nvs.set_u32("refresh", settings.refresh)?;
+    Ok(())
+}
+
+/// open the `access_point` with the provisioning form. Every dns request is answered with the address of the device,
+/// so most phones show the form directly after connecting. After the settings are saved the device restarts.
+/// This only returns on errors or when nothing was saved within the `timeout`.
+pub fn run(
+    modem: WifiModem,
+    sysloop: EspSystemEventLoop,
+    partition: EspDefaultNvsPartition,
+    access_point: &AccessPoint,
+    timeout: Option<Duration>,
+) -> anyhow::Result<()> {
+    let started = Instant::now();
+    let mut esp_wifi = EspWifi::new(modem, sysloop.clone(), Some(partition.clone()))?;
+    let mut wifi = BlockingWifi::wrap(&mut esp_wifi, sysloop)?;
+
+    wifi.set_configuration(&Configuration::AccessPoint(access_point_configuration(
+        access_point,
+        1,
+    )?))?;
+    wifi.start()?;
+    wifi.wait_netif_up()?;
+
+    let ip = wifi.wifi().ap_netif().get_ip_info()?.ip;
+    log::info!(
+        "provisioning access point {} started on {ip}",
+        access_point.ssid
+    );
+
+    let mut server = EspHttpServer::new(&HttpConfiguration {
+        uri_match_wildcard: true,
+        ..Default::default()
+    })?;
+
+    let current = load(&partition).ok().flatten();
+    server.fn_handler("/*", Method::Get, move |request| {
+        let page = form_page(current.as_ref(), None);
+        request
+            .into_response(200, None, &[("Content-Type", "text/html; charset=utf-8")])?
+            .write_all(page.as_bytes())?;
+        Ok::<(), anyhow::Error>(())
+    })?;
+
+    server.fn_handler("/*", Method::Post, move |mut request| {
+        let mut body = Vec::new();
+        let mut buffer = [0u8; 256];
+        loop {
+            let length = request.read(&mut buffer)?;
+            if length == 0 || body.len() > 1024 {
+                break;
+            }
+            body.extend_from_slice(&buffer[..length]);
+        }
+
+        match Settings::from_form(&body) {
+            Ok(settings) => {
+                save(&partition, &settings)?;
+                log::info!("saved provisioning for network {}", settings.ssid);
+                request
+                    .into_response(200, None, &[("Content-Type", "text/html; charset=utf-8")])?
+                    .write_all(saved_page().as_bytes())?;
+                std::thread::spawn(|| {
+                    std::thread::sleep(Duration::from_secs(2));
+                    esp_idf_svc::hal::reset::restart();
+                });
+            }
+            Err(error) => {
+                log::warn!("invalid provisioning form: {error}");
+                request
+                    .into_response(400, None, &[("Content-Type", "text/html; charset=utf-8")])?
+                    .write_all(form_page(None, Some(&error)).as_bytes())?;
+            }
+        }
+        Ok::<(), anyhow::Error>(())
+    })?;
+
+    // captive portal dns, the http server and the access point live as long as this loop
+    let socket = UdpSocket::bind("0.0.0.0:53")?;
+    socket.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut buffer = [0u8; 512];
+    loop {
+        if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+            log::info!("provisioning access point closed after {timeout:?}");
+            return Ok(());
+        }
+        let (length, source) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                continue
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if let Some(response) = dns_response(&buffer[..length], ip.octets()) {
+            socket.send_to(&response, source)?;
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use esp_idf_svc::sys::*;
+
+use crate::{board::Board, wake};
+
+/// release the pins held or isolated during the deep sleep, so they can be driven again
+pub fn wake_up(board: &Board) {
+    for pin in board.pins() {
+        unsafe {
+            gpio_hold_dis(pin);
+            if rtc_gpio_is_valid_gpio(pin) {
+                rtc_gpio_hold_dis(pin);
+                rtc_gpio_deinit(pin);
+            }
+        }
+    }
+    unsafe { gpio_deep_sleep_hold_dis() };
+}
+
+/// switch everything off and sleep until `until`, or until reset without it. Used by every sleep path:
+/// - the power pin of the panel is driven inactive and held there during the deep sleep
+/// - the other pins of the panel are isolated, so they do not float and leak current
+/// - the button is armed as wake source
+///
+/// The panel itself has to be put to sleep before, which `update_display` does after every refresh.
+pub fn deep_sleep(board: Option<&Board>, until: Option<DateTime<Utc>>) -> ! {
+    if let Some(board) = board {
+        if let Some(pin) = board.power {
+            let level = if board.power_active_high { 0 } else { 1 };
+            unsafe {
+                gpio_set_direction(pin, gpio_mode_t_GPIO_MODE_OUTPUT);
+                gpio_set_level(pin, level);
+                gpio_hold_en(pin);
+                gpio_deep_sleep_hold_en();
+            }
+        }
+        for pin in board
+            .pins()
+            .into_iter()
+            .filter(|pin| Some(*pin) != board.power && Some(*pin) != board.button)
+        {
+            unsafe {
+                if rtc_gpio_is_valid_gpio(pin) {
+                    rtc_gpio_isolate(pin);
+                } else {
+                    gpio_reset_pin(pin);
+                }
+            }
+        }
+        wake::enable_button(board);
+    }
+
+    log::info!("awake for {} ms", unsafe { esp_timer_get_time() } / 1000);
+    match until {
+        Some(until) => {
+            let sleep_time = until.signed_duration_since(Utc::now());
+            log::warn!("sleeping now for {}", sleep_time);
+            unsafe {
+                esp_deep_sleep(sleep_time.num_microseconds().unwrap_or_default().max(0) as u64)
+            }
+        }
+        None => {
+            log::warn!("sleeping until reset");
+            unsafe { esp_deep_sleep_start() }
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use chrono::TimeDelta;
+use embedded_graphics::prelude::*;
+use epd_waveshare::color::TriColor;
+use u8g2_fonts::{
+    fonts::u8g2_font_helvB14_tf,
+    types::{FontColor, HorizontalAlignment, VerticalPosition},
+    FontRenderer,
+};
+
+use crate::{
+    config::AccessPoint,
+    constants::{DISPLAY_HEIGHT, DISPLAY_WIDTH},
+    locale::Language,
+    schedule::RefreshPolicy,
+    secret::Secret,
+    weather::Location,
+};
+
+/// address of the provisioning form. The captive portal answers every dns query with its own address, so any name
+/// opens the form once connected to the provisioning access point.
+pub const SETUP_URL: &str = "http://esp-weather.setup/";
+
+/// characters of the generated passphrase, without the ones which are easily confused on the display
+const PASSPHRASE_CHARACTERS: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// WPA2 passphrase of the provisioning access point without `PROVISIONING_PASS`. It is generated from `random` for
+/// every portal and shown on the provisioning screen, so the wifi password is never sent over an open access point.
+pub fn generate_passphrase(mut random: impl FnMut() -> u32) -> String {
+    (0..10)
+        .map(|_| PASSPHRASE_CHARACTERS[random() as usize % PASSPHRASE_CHARACTERS.len()] as char)
+        .collect()
+}
+
+/// settings entered in the provisioning form and stored in the nvs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub ssid: String,
+    pub password: Secret,
+    pub location: Location,
+    /// refresh interval in minutes
+    pub refresh: u32,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum ProvisioningError {
+    #[error("The network name is missing")]
+    MissingSsid,
+    #[error("The network name is longer than 32 bytes")]
+    SsidTooLong,
+    #[error("The password needs 8 to 64 characters or must be empty for open networks")]
+    InvalidPassword,
+    #[error("The location must be latitude,longitude like 50.11,8.68")]
+    InvalidLocation,
+    #[error("The refresh interval must be between 5 and 1440 minutes")]
+    InvalidRefresh,
+}
+
+impl Settings {
+    /// parse and validate the `application/x-www-form-urlencoded` body of the form
+    pub fn from_form(body: &[u8]) -> Result<Self, ProvisioningError> {
+        let mut ssid = String::new();
+        let mut password = String::new();
+        let mut location = String::new();
+        let mut refresh = String::new();
+        for (key, value) in form_urlencoded::parse(body) {
+            match key.as_ref() {
+                "ssid" => ssid = value.trim().to_string(),
+                "password" => password = value.into_owned(),
+                "location" => location = value.into_owned(),
+                "refresh" => refresh = value.into_owned(),
+                _ => {}
+            }
+        }
+
+        if ssid.is_empty() {
+            return Err(ProvisioningError::MissingSsid);
+        }
+        if ssid.len() > 32 {
+            return Err(ProvisioningError::SsidTooLong);
+        }
+        if !password.is_empty() && !(8..=64).contains(&password.len()) {
+            return Err(ProvisioningError::InvalidPassword);
+        }
+        let location = Location::from_code(&location).ok_or(ProvisioningError::InvalidLocation)?;
+        let refresh = (refresh.trim().parse::<u32>().ok())
+            .filter(|refresh| (5..=1440).contains(refresh))
+            .ok_or(ProvisioningError::InvalidRefresh)?;
+
+        Ok(Self {
+            ssid,
+            password: Secret::new(password),
+            location,
+            refresh,
+        })
+    }
+
+    pub fn refresh_policy(&self) -> RefreshPolicy {
+        RefreshPolicy::Interval(TimeDelta::minutes(self.refresh as i64))
+    }
+}
+
+/// the provisioning form, prefilled with the current settings. The password is never sent back.
+pub fn form_page(settings: Option<&Settings>, error: Option<&ProvisioningError>) -> String {
+    let (ssid, location, refresh) = match settings {
+        Some(settings) => (
+            escape(&settings.ssid),
+            settings.location.to_string(),
+            settings.refresh.to_string(),
+        ),
+        None => (String::new(), String::new(), "60".to_string()),
+    };
+    let error = error
+        .map(|error| format!("<p style=\"color:red\">{}</p>", escape(&error.to_string())))
+        .unwrap_or_default();
+    format!(
+        r#"<!DOCTYPE html>
+<html><head><meta charset="utf-8"><meta name="viewport" content="width=device-width, initial-scale=1"><title>esp-weather</title></head>
+<body><h1>esp-weather setup</h1>{error}
+<form method="post" action="/">
+<p><label>Wifi name<br><input name="ssid" maxlength="32" value="{ssid}" required></label></p>
+<p><label>Wifi password<br><input name="password" type="password" maxlength="64"></label></p>
+<p><label>Location (latitude,longitude, add ,air for the air quality and pollen)<br><input name="location" value="{location}" required></label></p>
+<p><label>Refresh interval in minutes<br><input name="refresh" type="number" min="5" max="1440" value="{refresh}" required></label></p>
+<p><button type="submit">Save and restart</button></p>
+</form></body></html>"#
+    )
+}
+
+pub fn saved_page() -> &'static str {
+    r#"<!DOCTYPE html>
+<html><head><meta charset="utf-8"><title>esp-weather</title></head>
+<body><h1>Saved</h1><p>The display restarts and connects to the configured wifi.</p></body></html>"#
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// answer to a dns query which resolves every name to `ip`, so phones open the form as captive portal
+pub fn dns_response(query: &[u8], ip: [u8; 4]) -> Option<Vec<u8>> {
+    const HEADER: usize = 12;
+
+    // only standard queries with at least one question
+    let flags = u16::from_be_bytes([*query.get(2)?, *query.get(3)?]);
+    let questions = u16::from_be_bytes([*query.get(4)?, *query.get(5)?]);
+    if flags & 0xf800 != 0 || questions == 0 {
+        return None;
+    }
+
+    // the first question is the name followed by type and class
+    let mut end = HEADER;
+    loop {
+        let length = *query.get(end)? as usize;
+        end += 1;
+        if length == 0 {
+            break;
+        }
+        if length & 0xc0 != 0 {
+            return None;
+        }
+        end += length;
+    }
+    end += 4;
+    let question = query.get(HEADER..end)?;
+
+    let mut response = Vec::with_capacity(end + 16);
+    response.extend_from_slice(&query[0..2]);
+    // response, authoritative, keep recursion desired
+    response.extend_from_slice(&(0x8400 | (flags & 0x0100) | 0x0080).to_be_bytes());
+    response.extend_from_slice(&1u16.to_be_bytes());
+    response.extend_from_slice(&1u16.to_be_bytes());
+    response.extend_from_slice(&0u16.to_be_bytes());
+    response.extend_from_slice(&0u16.to_be_bytes());
+    response.extend_from_slice(question);
+    // pointer to the name of the question
+    response.extend_from_slice(&[0xc0, HEADER as u8]);
+    // type A, class IN
+    response.extend_from_slice(&1u16.to_be_bytes());
+    response.extend_from_slice(&1u16.to_be_bytes());
+    // ttl
+    response.extend_from_slice(&60u32.to_be_bytes());
+    response.extend_from_slice(&4u16.to_be_bytes());
+    response.extend_from_slice(&ip);
+    Some(response)
+}
+
+/// hint on the display how to reach the provisioning form
+pub fn draw_provisioning_screen<Display>(
+    display: &mut Display,
+    access_point: &AccessPoint,
+    language: Language,
+) where
+    Display: DrawTarget<Color = TriColor>,
+    Display::Error: std::fmt::Debug,
+{
+    display.clear(TriColor::White).unwrap();
+    let center = Point::new(DISPLAY_WIDTH as i32 / 2, DISPLAY_HEIGHT as i32 / 2);
+    let password = (access_point.password.as_ref())
+        .map(|password| format!("{} {}", language.password(), password.expose()))
+        .unwrap_or_default();
+    let lines = [
+        (language.connect_to_setup(), TriColor::Black),
+        (access_point.ssid.as_str(), TriColor::Chromatic),
+        (password.as_str(), TriColor::Chromatic),
+        (language.open_setup_page(), TriColor::Black),
+    ];
+    for (i, (line, color)) in lines.into_iter().enumerate() {
+        FontRenderer::new::<u8g2_font_helvB14_tf>()
+            .render_aligned(
+                line,
+                center + Point::new(0, i as i32 * 30 - 45),
+                VerticalPosition::Baseline,
+                HorizontalAlignment::Center,
+                FontColor::Transparent(color),
+                display,
+            )
+            .unwrap();
+    }
+}
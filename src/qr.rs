@@ -0,0 +1,87 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+use epd_waveshare::color::TriColor;
+use qrcodegen::QrCodeEcc;
+
+/// modules of white around the code recommended by the standard, scanners need less on a white screen
+pub const QUIET_ZONE: u32 = 4;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum QrError {
+    #[error("the text is too long for a qr code")]
+    TooLong,
+}
+
+/// qr code with medium error correction, which is raised if the text fits anyway
+#[derive(Clone, PartialEq, Eq)]
+pub struct QrCode {
+    code: qrcodegen::QrCode,
+}
+
+impl std::fmt::Debug for QrCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QrCode")
+            .field("version", &self.code.version().value())
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+impl QrCode {
+    pub fn encode(text: &str) -> Result<Self, QrError> {
+        let code = qrcodegen::QrCode::encode_text(text, QrCodeEcc::Medium)
+            .map_err(|_| QrError::TooLong)?;
+        Ok(Self { code })
+    }
+
+    /// modules per side without the quiet zone
+    pub fn size(&self) -> u32 {
+        self.code.size() as u32
+    }
+
+    pub fn is_dark(&self, x: u32, y: u32) -> bool {
+        self.code.get_module(x as i32, y as i32)
+    }
+
+    /// pixels per side with the quiet zone
+    pub fn width(&self, module_size: u32, quiet_zone: u32) -> u32 {
+        (self.size() + 2 * quiet_zone) * module_size
+    }
+
+    /// largest module size for which the code with its quiet zone fits into `max_width` pixels,
+    /// `None` if not even single pixel modules fit
+    pub fn module_size_for(&self, max_width: u32, quiet_zone: u32) -> Option<u32> {
+        let module_size = max_width / (self.size() + 2 * quiet_zone);
+        (module_size > 0).then_some(module_size)
+    }
+
+    /// draw the code with `module_size` pixels per module, `top_left` is the corner of the quiet zone.
+    /// The quiet zone is cleared so the code can be drawn over other content.
+    pub fn draw<Display>(
+        &self,
+        display: &mut Display,
+        top_left: Point,
+        module_size: u32,
+        quiet_zone: u32,
+    ) where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        let width = self.width(module_size, quiet_zone);
+        display
+            .fill_solid(
+                &Rectangle::new(top_left, Size::new_equal(width)),
+                TriColor::White,
+            )
+            .unwrap();
+        let origin = top_left + Point::new_equal((quiet_zone * module_size) as i32);
+        for y in 0..self.size() {
+            for x in (0..self.size()).filter(|&x| self.is_dark(x, y)) {
+                let module = Rectangle::new(
+                    origin + Point::new((x * module_size) as i32, (y * module_size) as i32),
+                    Size::new_equal(module_size),
+                );
+                display.fill_solid(&module, TriColor::Black).unwrap();
+            }
+        }
+    }
+}
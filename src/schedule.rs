@@ -0,0 +1,120 @@
+use chrono::{DateTime, Days, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc};
+
+/// when the display wakes up from deep sleep to refresh the forecast.
+/// Selected with the `REFRESH` environment variable:
+/// - `every 30m` or `every 2h` for a fixed interval
+/// - `at 06:00,12:00,18:00` for a list of wall clock times
+/// - `every 1h quiet 22:00-06:00` for a fixed interval without refreshes during the quiet hours
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefreshPolicy {
+    Interval(TimeDelta),
+    Times(Vec<NaiveTime>),
+    QuietHours {
+        interval: TimeDelta,
+        start: NaiveTime,
+        end: NaiveTime,
+    },
+}
+
+impl Default for RefreshPolicy {
+    fn default() -> Self {
+        Self::QuietHours {
+            interval: TimeDelta::hours(1),
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+        }
+    }
+}
+
+impl RefreshPolicy {
+    pub fn from_code(code: &str) -> Option<Self> {
+        let words = code.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["every", interval] => Some(Self::Interval(parse_interval(interval)?)),
+            ["at", times] => {
+                let times = (times.split(','))
+                    .map(parse_time)
+                    .collect::<Option<Vec<_>>>()?;
+                (!times.is_empty()).then_some(Self::Times(times))
+            }
+            ["every", interval, "quiet", range] => {
+                let (start, end) = range.split_once('-')?;
+                Some(Self::QuietHours {
+                    interval: parse_interval(interval)?,
+                    start: parse_time(start)?,
+                    end: parse_time(end)?,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// next time to wake up after `now`. The wall clock times are interpreted in `timezone`, which should be the timezone of the forecast.
+    pub fn next_wake<Tz: TimeZone>(&self, now: DateTime<Utc>, timezone: &Tz) -> DateTime<Utc> {
+        match self {
+            Self::Interval(interval) => now + *interval,
+            Self::Times(times) => next_time(now, timezone, times),
+            Self::QuietHours {
+                interval,
+                start,
+                end,
+            } => {
+                let wake = now + *interval;
+                let local = wake.with_timezone(timezone).time();
+                let quiet = if start <= end {
+                    local >= *start && local < *end
+                } else {
+                    local >= *start || local < *end
+                };
+                if quiet {
+                    // the end of the quiet hours is the first wall clock time `end` after the planned wake up
+                    next_time(wake, timezone, &[*end])
+                } else {
+                    wake
+                }
+            }
+        }
+    }
+}
+
+/// first of the wall clock `times` after `now`
+fn next_time<Tz: TimeZone>(
+    now: DateTime<Utc>,
+    timezone: &Tz,
+    times: &[NaiveTime],
+) -> DateTime<Utc> {
+    let today = now.with_timezone(timezone).date_naive();
+    // starting a day earlier catches times which are already tomorrow in utc
+    (0..=2)
+        .filter_map(|day| today.pred_opt()?.checked_add_days(Days::new(day)))
+        .flat_map(|date| times.iter().map(move |time| date.and_time(*time)))
+        .filter_map(|local| resolve_local(timezone, local))
+        .filter(|wake| *wake > now)
+        .min()
+        .unwrap_or(now + TimeDelta::days(1))
+}
+
+/// convert a wall clock time to utc. During the fall back transition the earlier of both times is taken,
+/// times skipped by the spring forward transition are moved to the first valid time after the gap.
+fn resolve_local<Tz: TimeZone>(timezone: &Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    (0..=8)
+        .map(|step| local + TimeDelta::minutes(15 * step))
+        .find_map(|local| timezone.from_local_datetime(&local).earliest())
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn parse_interval(value: &str) -> Option<TimeDelta> {
+    let (number, unit) = value.split_at(value.find(|c: char| !c.is_ascii_digit())?);
+    let number = number.parse::<i64>().ok()?;
+    let seconds = match unit {
+        "m" | "min" => number.checked_mul(60)?,
+        "h" => number.checked_mul(3600)?,
+        _ => return None,
+    };
+    let interval = TimeDelta::try_seconds(seconds)?;
+    (interval > TimeDelta::zero()).then_some(interval)
+}
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+
+use embedded_svc::{
+    http::{client::Client, Method},
+    io::Read,
+};
+use esp_idf_svc::{
+    eventloop::EspSystemEventLoop,
+    hal::modem::WifiModem,
+    http::client::{Configuration as HttpConfiguration, EspHttpConnection},
+    sntp::{EspSntp, SntpConf, SyncStatus},
+    wifi::EspWifi,
+};
+
+use crate::{
+    clock::{self, TimeSource},
+    config::Config,
+    network::NetworkStatus,
+    wifi,
+};
+
+/// largest response body accepted by `get`
+const MAX_BODY: usize = 1 << 16;
+
+/// body and `Date` header of a successful request
+pub struct HttpResponse {
+    pub body: Vec<u8>,
+    pub date: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// wifi, time sync and http client of one wake cycle. It is created once after boot, so retries reuse the
+/// driver instead of taking the modem and the event loop again.
+pub struct Session {
+    wifi: Box<EspWifi<'static>>,
+    sysloop: EspSystemEventLoop,
+    config: Config,
+    sntp: Option<EspSntp<'static>>,
+    client: Option<Client<EspHttpConnection>>,
+    status: Option<NetworkStatus>,
+}
+
+impl Session {
+    /// initialize the wifi driver without connecting
+    pub fn new(
+        modem: WifiModem,
+        sysloop: EspSystemEventLoop,
+        config: &Config,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            wifi: Box::new(EspWifi::new(modem, sysloop.clone(), None)?),
+            sysloop,
+            config: config.clone(),
+            sntp: None,
+            client: None,
+            status: None,
+        })
+    }
+
+    /// connect to the best known network, does nothing if already connected
+    pub fn connect(&mut self) -> anyhow::Result<&NetworkStatus> {
+        if self.status.is_none() || !self.wifi.is_up()? {
+            let status = wifi::connect(
+                &mut self.wifi,
+                self.sysloop.clone(),
+                &self.config.networks,
+                self.config.wifi_timeout,
+                self.config.access_point.as_ref(),
+                self.config.wifi_reuse_lease,
+            )?;
+            log::info!("connected to {}", status.ssid);
+            self.status = Some(status);
+        }
+        Ok(self.status.as_ref().unwrap())
+    }
+
+    pub fn status(&self) -> Option<&NetworkStatus> {
+        self.status.as_ref()
+    }
+
+    /// start sntp once with the configured servers and wait for the first sync. After the timeout the system time
+    /// is used if the rtc kept it from an earlier wake cycle, `None` if the clock was never set.
+    pub async fn sync_time(&mut self) -> Option<TimeSource> {
+        if self.sntp.is_none() {
+            let mut conf = SntpConf::default();
+            for (slot, server) in conf.servers.iter_mut().zip(&self.config.ntp_servers) {
+                *slot = server.as_str();
+            }
+            match EspSntp::new(&conf) {
+                Ok(sntp) => self.sntp = Some(sntp),
+                Err(err) => log::error!("failed to start sntp {err}"),
+            }
+        }
+
+        if let Some(sntp) = &self.sntp {
+            let deadline = Instant::now() + self.config.sntp_timeout;
+            while Instant::now() < deadline {
+                if sntp.get_sync_status() == SyncStatus::Completed {
+                    return Some(TimeSource::Ntp);
+                }
+                smol::Timer::after(Duration::from_millis(200)).await;
+            }
+            log::warn!(
+                "no time sync after {} seconds",
+                self.config.sntp_timeout.as_secs()
+            );
+        }
+
+        clock::is_plausible(chrono::Utc::now()).then_some(TimeSource::Rtc)
+    }
+
+    pub fn get(&mut self, url: &str) -> anyhow::Result<HttpResponse> {
+        if self.client.is_none() {
+            let connection = EspHttpConnection::new(&HttpConfiguration {
+                crt_bundle_attach: Some(esp_idf_svc::sys::esp_crt_bundle_attach),
+                timeout: Some(self.config.http_timeout),
+                ..Default::default()
+            })?;
+            self.client = Some(Client::wrap(connection));
+        }
+        let client = self.client.as_mut().unwrap();
+
+        log::info!("starting request");
+        let request = client.request(Method::Get, url, &[("Accept", "application/json")])?;
+        let mut response = request.submit()?;
+
+        let status = response.status();
+        log::info!("status: {status}");
+        if !(200..300).contains(&status) {
+            anyhow::bail!("request failed with status {status}");
+        }
+        let date = response.header("Date").and_then(clock::parse_http_date);
+
+        let mut body = Vec::new();
+        let mut buffer = [0u8; 1024];
+        loop {
+            let length = response.read(&mut buffer)?;
+            if length == 0 {
+                break;
+            }
+            if body.len() + length > MAX_BODY {
+                anyhow::bail!("response is larger than {MAX_BODY} bytes");
+            }
+            body.extend_from_slice(&buffer[..length]);
+        }
+        Ok(HttpResponse { body, date })
+    }
+
+    /// stop the http client, time sync and wifi. A later `connect` starts over with a scan.
+    pub fn disconnect(&mut self) -> anyhow::Result<()> {
+        self.client = None;
+        self.sntp = None;
+        self.status = None;
+        if self.wifi.is_started()? {
+            self.wifi.disconnect()?;
+            self.wifi.stop()?;
+        }
+        Ok(())
+    }
+
+    /// release the wifi driver, the returned event loop can be used for the next driver
+    pub fn close(mut self) -> EspSystemEventLoop {
+        if let Err(err) = self.disconnect() {
+            log::warn!("failed to stop the wifi {err}");
+        }
+        self.sysloop
+    }
+}
@@ -0,0 +1,82 @@
+use chrono::NaiveDateTime;
+use embedded_graphics::{prelude::*, primitives::PrimitiveStyle};
+use epd_waveshare::color::TriColor;
+use u8g2_fonts::{
+    fonts::u8g2_font_helvB10_tf,
+    types::{FontColor, HorizontalAlignment, VerticalPosition},
+    FontRenderer,
+};
+
+use crate::{
+    battery::BatteryLevel, clock::TimeSource, framebuffer::Region, locale::Language,
+    network::NetworkStatus,
+};
+
+/// header with the date and time of the last update, the battery and the wifi. It is drawn with the forecast
+/// and redrawn alone for the partial refreshes in between.
+#[derive(Debug, Clone)]
+pub struct StatusStrip<'a> {
+    /// area of the strip, it is cleared before drawing
+    pub region: Region,
+    /// local time of the update
+    pub time: NaiveDateTime,
+    pub language: Language,
+    pub time_source: TimeSource,
+    pub battery: Option<BatteryLevel>,
+    pub network: Option<&'a NetworkStatus>,
+    /// draw everything in black, the partial refreshes only send the black plane
+    pub mono: bool,
+}
+
+impl StatusStrip<'_> {
+    pub fn draw<Display>(&self, display: &mut Display)
+    where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        let Region { x, y, width, .. } = self.region;
+        let highlight = if self.mono {
+            TriColor::Black
+        } else {
+            TriColor::Chromatic
+        };
+        let (left, center, right, top) = (
+            x as i32,
+            (x + width / 2) as i32,
+            (x + width) as i32,
+            y as i32,
+        );
+        self.region
+            .rectangle()
+            .into_styled(PrimitiveStyle::with_fill(TriColor::White))
+            .draw(display)
+            .unwrap();
+
+        let today = format!(
+            "{} {}",
+            self.language.format_date(self.time.date()),
+            self.time.format("%H:%M")
+        );
+        FontRenderer::new::<u8g2_font_helvB10_tf>()
+            .render_aligned(
+                today.as_str(),
+                Point::new(center, top + 30),
+                VerticalPosition::Baseline,
+                HorizontalAlignment::Center,
+                FontColor::Transparent(TriColor::Black),
+                display,
+            )
+            .unwrap();
+
+        self.time_source
+            .draw(display, Point::new(center, top + 43), highlight);
+
+        if let Some(battery) = self.battery {
+            battery.draw_gauge(display, Point::new(right - 10, top + 18), highlight);
+        }
+
+        if let Some(network) = self.network {
+            network.draw(display, Point::new(left + 10, top + 25));
+        }
+    }
+}
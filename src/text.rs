@@ -0,0 +1,131 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+use epd_waveshare::color::TriColor;
+use u8g2_fonts::{
+    types::{FontColor, HorizontalAlignment, VerticalPosition},
+    FontRenderer,
+};
+
+/// appended to truncated text. The u8g2 fonts only have latin-1, so it is not `…`.
+pub const ELLIPSIS: &str = "...";
+
+/// width of `text` in pixels when drawn with `font`, unknown glyphs count as zero
+pub fn text_width(font: &FontRenderer, text: &str) -> u32 {
+    font.get_rendered_dimensions(text, Point::zero(), VerticalPosition::Top)
+        .map_or(0, |dimensions| dimensions.advance.x.max(0) as u32)
+}
+
+/// shorten `text` with an ellipsis so it is at most `max_width` wide. `measure` returns the width of a text.
+pub fn truncate(text: &str, max_width: u32, measure: impl Fn(&str) -> u32) -> String {
+    if measure(text) <= max_width {
+        return text.to_string();
+    }
+    (text.char_indices().rev())
+        .map(|(end, _)| format!("{}{ELLIPSIS}", text[..end].trim_end()))
+        .find(|shortened| measure(shortened) <= max_width)
+        .unwrap_or_default()
+}
+
+/// break `text` at the spaces into lines of at most `max_width`, line breaks in the text are kept.
+/// Words wider than a line are truncated. If there are more than `max_lines` lines the last one shown ends
+/// with an ellipsis.
+pub fn wrap(
+    text: &str,
+    max_width: u32,
+    max_lines: usize,
+    measure: impl Fn(&str) -> u32,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{line} {word}")
+            };
+            if measure(&candidate) <= max_width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            line = truncate(word, max_width, &measure);
+        }
+        lines.push(line);
+    }
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            *last = truncate(&format!("{last}{ELLIPSIS}"), max_width, &measure);
+        }
+    }
+    lines
+}
+
+/// text in a rectangle, wrapped at the words and drawn with the largest of `fonts` which shows the whole text.
+/// If even the smallest font is too large the text is truncated with an ellipsis.
+#[derive(Clone, Copy)]
+pub struct TextBox<'a> {
+    pub area: Rectangle,
+    /// candidates from the largest to the smallest
+    pub fonts: &'a [FontRenderer],
+    pub alignment: HorizontalAlignment,
+    /// center the lines vertically instead of starting at the top
+    pub middle: bool,
+    pub color: TriColor,
+}
+
+impl TextBox<'_> {
+    /// the font and the lines of `text`
+    pub fn layout(&self, text: &str) -> Option<(&FontRenderer, Vec<String>)> {
+        let width = self.area.size.width;
+        let mut fitted = None;
+        for font in self.fonts {
+            let max_lines = (self.area.size.height / font.get_default_line_height().max(1)).max(1);
+            let measure = |text: &str| text_width(font, text);
+            let lines = wrap(text, width, usize::MAX, measure);
+            let complete = lines.len() <= max_lines as usize
+                && text.split_whitespace().all(|word| measure(word) <= width);
+            if complete {
+                return Some((font, lines));
+            }
+            fitted = Some((font, wrap(text, width, max_lines as usize, measure)));
+        }
+        fitted
+    }
+
+    /// draw `text` and return the number of lines
+    pub fn draw<Display>(&self, display: &mut Display, text: &str) -> usize
+    where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        let Some((font, lines)) = self.layout(text) else {
+            return 0;
+        };
+        let line_height = font.get_default_line_height() as i32;
+        let x = match self.alignment {
+            HorizontalAlignment::Left => self.area.top_left.x,
+            HorizontalAlignment::Center => self.area.center().x,
+            HorizontalAlignment::Right => self.area.top_left.x + self.area.size.width as i32 - 1,
+        };
+        let top = if self.middle {
+            self.area.center().y - line_height * lines.len() as i32 / 2
+        } else {
+            self.area.top_left.y
+        };
+        for (i, line) in lines.iter().enumerate() {
+            font.render_aligned(
+                line.as_str(),
+                Point::new(x, top + line_height * i as i32),
+                VerticalPosition::Top,
+                self.alignment,
+                FontColor::Transparent(self.color),
+                display,
+            )
+            .unwrap();
+        }
+        lines.len()
+    }
+}
@@ -0,0 +1,304 @@
+use std::fmt::Display;
+
+use serde::Deserialize;
+
+#[derive(thiserror::Error, Debug)]
+#[error("unknown unit {0}")]
+pub struct UnknownUnit(String);
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(try_from = "String")]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    /// value of the open-meteo `temperature_unit` parameter
+    pub fn query(&self) -> &'static str {
+        match self {
+            Self::Celsius => "celsius",
+            Self::Fahrenheit => "fahrenheit",
+        }
+    }
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Celsius => "°C",
+            Self::Fahrenheit => "°F",
+        }
+    }
+    /// used to scale the graphs independent of the unit
+    pub fn to_celsius(&self, value: f32) -> f32 {
+        match self {
+            Self::Celsius => value,
+            Self::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+        }
+    }
+    pub fn from_celsius(&self, value: f32) -> f32 {
+        match self {
+            Self::Celsius => value,
+            Self::Fahrenheit => value * 9.0 / 5.0 + 32.0,
+        }
+    }
+}
+
+impl TryFrom<String> for TemperatureUnit {
+    type Error = UnknownUnit;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "°C" | "celsius" => Ok(Self::Celsius),
+            "°F" | "fahrenheit" => Ok(Self::Fahrenheit),
+            _ => Err(UnknownUnit(value)),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(try_from = "String")]
+pub enum PrecipitationUnit {
+    #[default]
+    Millimeter,
+    Inch,
+}
+
+impl PrecipitationUnit {
+    /// value of the open-meteo `precipitation_unit` parameter
+    pub fn query(&self) -> &'static str {
+        match self {
+            Self::Millimeter => "mm",
+            Self::Inch => "inch",
+        }
+    }
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Millimeter => "mm",
+            Self::Inch => "in",
+        }
+    }
+    /// decimal places worth showing for this unit
+    pub fn precision(&self) -> usize {
+        match self {
+            Self::Millimeter => 1,
+            Self::Inch => 2,
+        }
+    }
+    /// used to scale the graphs independent of the unit
+    pub fn to_millimeter(&self, value: f32) -> f32 {
+        match self {
+            Self::Millimeter => value,
+            Self::Inch => value * 25.4,
+        }
+    }
+}
+
+impl TryFrom<String> for PrecipitationUnit {
+    type Error = UnknownUnit;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "mm" => Ok(Self::Millimeter),
+            "inch" | "in" => Ok(Self::Inch),
+            _ => Err(UnknownUnit(value)),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(try_from = "String")]
+pub enum WindSpeedUnit {
+    #[default]
+    KilometersPerHour,
+    MetersPerSecond,
+    MilesPerHour,
+    Knots,
+}
+
+impl WindSpeedUnit {
+    /// value of the open-meteo `wind_speed_unit` parameter
+    pub fn query(&self) -> &'static str {
+        match self {
+            Self::KilometersPerHour => "kmh",
+            Self::MetersPerSecond => "ms",
+            Self::MilesPerHour => "mph",
+            Self::Knots => "kn",
+        }
+    }
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::KilometersPerHour => "km/h",
+            Self::MetersPerSecond => "m/s",
+            Self::MilesPerHour => "mph",
+            Self::Knots => "kn",
+        }
+    }
+}
+
+impl TryFrom<String> for WindSpeedUnit {
+    type Error = UnknownUnit;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "km/h" | "kmh" => Ok(Self::KilometersPerHour),
+            "m/s" | "ms" => Ok(Self::MetersPerSecond),
+            "mp/h" | "mph" => Ok(Self::MilesPerHour),
+            "kn" | "knots" => Ok(Self::Knots),
+            _ => Err(UnknownUnit(value)),
+        }
+    }
+}
+
+/// units requested from open-meteo. Selected with the `UNITS` environment variable (`metric` or `imperial`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Units {
+    pub temperature: TemperatureUnit,
+    pub precipitation: PrecipitationUnit,
+    pub wind_speed: WindSpeedUnit,
+}
+
+impl Units {
+    pub fn metric() -> Self {
+        Self::default()
+    }
+
+    pub fn imperial() -> Self {
+        Self {
+            temperature: TemperatureUnit::Fahrenheit,
+            precipitation: PrecipitationUnit::Inch,
+            wind_speed: WindSpeedUnit::MilesPerHour,
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_lowercase().as_str() {
+            "metric" | "si" => Some(Self::metric()),
+            "imperial" | "us" => Some(Self::imperial()),
+            _ => None,
+        }
+    }
+
+    /// query parameters for the open-meteo forecast api
+    pub fn query(&self) -> String {
+        format!(
+            "temperature_unit={}&precipitation_unit={}&wind_speed_unit={}",
+            self.temperature.query(),
+            self.precipitation.query(),
+            self.wind_speed.query()
+        )
+    }
+}
+
+/// temperature with the unit open-meteo returned it in.
+///
+/// `{}` formats the value with the unit symbol like `21°C`, `{:#}` only like `21°` for dense graph labels.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Temperature {
+    value: f32,
+    unit: TemperatureUnit,
+}
+
+impl Temperature {
+    pub fn new(value: f32, unit: TemperatureUnit) -> Self {
+        Self { value, unit }
+    }
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+    pub fn unit(&self) -> TemperatureUnit {
+        self.unit
+    }
+    pub fn celsius(&self) -> f32 {
+        self.unit.to_celsius(self.value)
+    }
+}
+
+impl Display for Temperature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            write!(f, "{:.0}°", self.value)
+        } else {
+            write!(f, "{:.0}{}", self.value, self.unit.symbol())
+        }
+    }
+}
+
+/// precipitation with the unit open-meteo returned it in.
+///
+/// `{}` formats the value with the unit symbol like `0.4 mm`, `{:#}` only the number like `0.4`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Precipitation {
+    value: f32,
+    unit: PrecipitationUnit,
+}
+
+impl Precipitation {
+    pub fn new(value: f32, unit: PrecipitationUnit) -> Self {
+        Self { value, unit }
+    }
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+    pub fn unit(&self) -> PrecipitationUnit {
+        self.unit
+    }
+    pub fn millimeter(&self) -> f32 {
+        self.unit.to_millimeter(self.value)
+    }
+}
+
+impl Display for Precipitation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precision = self.unit.precision();
+        if f.alternate() {
+            write!(f, "{:.*}", precision, self.value)
+        } else {
+            write!(f, "{:.*} {}", precision, self.value, self.unit.symbol())
+        }
+    }
+}
+
+/// wind speed with the unit open-meteo returned it in
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Speed {
+    value: f32,
+    unit: WindSpeedUnit,
+}
+
+impl Speed {
+    pub fn new(value: f32, unit: WindSpeedUnit) -> Self {
+        Self { value, unit }
+    }
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+    pub fn unit(&self) -> WindSpeedUnit {
+        self.unit
+    }
+}
+
+impl Display for Speed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0} {}", self.value, self.unit.symbol())
+    }
+}
+
+/// percentage between 0 and 100 like the precipitation probability or the cloud cover
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[serde(from = "f32")]
+pub struct Factor(f32);
+
+impl Factor {
+    pub fn value(&self) -> f32 {
+        self.0
+    }
+}
+
+impl From<f32> for Factor {
+    fn from(value: f32) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for Factor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0} %", self.0)
+    }
+}
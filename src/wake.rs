@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+#[cfg(target_os = "espidf")]
+use crate::board::Board;
+
+/// holding the button longer than this is a long press
+pub const LONG_PRESS: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Press {
+    Short,
+    Long,
+}
+
+impl Press {
+    pub fn from_duration(held: Duration) -> Self {
+        if held >= LONG_PRESS {
+            Self::Long
+        } else {
+            Self::Short
+        }
+    }
+}
+
+/// why the device is running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WakeCause {
+    /// power on or reset, nothing is known from before
+    Reset,
+    Timer,
+    Button(Press),
+    /// any other wake source of the chip
+    Other,
+}
+
+/// what the boot path does for a `WakeCause`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WakeAction {
+    /// the planned refresh, partial or full
+    Scheduled,
+    /// show the next page with a full refresh
+    NextPage,
+    /// open the provisioning access point
+    Provision,
+}
+
+impl WakeCause {
+    pub fn action(&self) -> WakeAction {
+        match self {
+            Self::Reset | Self::Timer | Self::Other => WakeAction::Scheduled,
+            Self::Button(Press::Short) => WakeAction::NextPage,
+            Self::Button(Press::Long) => WakeAction::Provision,
+        }
+    }
+
+    /// the wake source reported by the chip. After a button wake up the press is measured until the button is released,
+    /// at most for `LONG_PRESS`.
+    #[cfg(target_os = "espidf")]
+    pub fn read(board: &Board) -> Self {
+        use esp_idf_svc::sys::*;
+
+        #[allow(non_upper_case_globals)]
+        match unsafe { esp_sleep_get_wakeup_cause() } {
+            esp_sleep_source_t_ESP_SLEEP_WAKEUP_UNDEFINED => Self::Reset,
+            esp_sleep_source_t_ESP_SLEEP_WAKEUP_TIMER => Self::Timer,
+            esp_sleep_source_t_ESP_SLEEP_WAKEUP_EXT1 => {
+                let Some(pin) = board.button else {
+                    return Self::Other;
+                };
+                let pressed = std::time::Instant::now();
+                unsafe {
+                    rtc_gpio_deinit(pin);
+                    gpio_set_direction(pin, gpio_mode_t_GPIO_MODE_INPUT);
+                    gpio_pullup_en(pin);
+                }
+                // the button pulls the pin low
+                while unsafe { gpio_get_level(pin) } == 0 && pressed.elapsed() < LONG_PRESS {
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                Self::Button(Press::from_duration(pressed.elapsed()))
+            }
+            _ => Self::Other,
+        }
+    }
+}
+
+/// wake up when the button pulls its pin low
+#[cfg(target_os = "espidf")]
+pub fn enable_button(board: &Board) {
+    use esp_idf_svc::sys::*;
+
+    let Some(pin) = board.button else {
+        return;
+    };
+    let result = unsafe {
+        rtc_gpio_pullup_en(pin);
+        rtc_gpio_pulldown_dis(pin);
+        esp!(esp_sleep_enable_ext1_wakeup(
+            1 << pin,
+            esp_sleep_ext1_wakeup_mode_t_ESP_EXT1_WAKEUP_ANY_LOW
+        ))
+    };
+    if let Err(err) = result {
+        log::error!("gpio {pin} can not wake up the device {err}");
+    }
+}
@@ -0,0 +1,246 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use embedded_graphics::{
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+use epd_waveshare::color::TriColor;
+use serde::Deserialize;
+use u8g2_fonts::{
+    fonts::{u8g2_font_helvB10_tf, u8g2_font_helvB12_tf, u8g2_font_helvR08_tf},
+    types::HorizontalAlignment,
+    FontRenderer,
+};
+
+use crate::{layout::Area, locale::Language, text::TextBox};
+
+/// how dangerous the weather is, in the levels of the common alerting protocol
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(from = "String")]
+pub enum Severity {
+    /// also used for warnings without a known severity
+    Minor,
+    Moderate,
+    Severe,
+    Extreme,
+}
+
+impl From<String> for Severity {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "Moderate" => Self::Moderate,
+            "Severe" => Self::Severe,
+            "Extreme" => Self::Extreme,
+            _ => Self::Minor,
+        }
+    }
+}
+
+impl Severity {
+    pub fn name(&self, language: Language) -> &'static str {
+        match (self, language) {
+            (Self::Minor, Language::English) => "Weather warning",
+            (Self::Minor, Language::German) => "Wetterwarnung",
+            (Self::Moderate, Language::English) => "Moderate warning",
+            (Self::Moderate, Language::German) => "Markante Wetterwarnung",
+            (Self::Severe, Language::English) => "Severe warning",
+            (Self::Severe, Language::German) => "Unwetterwarnung",
+            (Self::Extreme, Language::English) => "Extreme warning",
+            (Self::Extreme, Language::German) => "Extremes Unwetter",
+        }
+    }
+}
+
+/// official warning for the configured region
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub headline: String,
+    /// kind of weather like `STURMBÖEN`
+    pub event: String,
+    pub severity: Severity,
+    /// name of the region
+    pub area: String,
+    pub onset: DateTime<Utc>,
+    /// `None` until further notice
+    pub expires: Option<DateTime<Utc>>,
+}
+
+impl Warning {
+    /// whether the warning is in effect or starts later
+    pub fn is_current(&self, now: DateTime<Utc>) -> bool {
+        self.expires.is_none_or(|expires| now < expires)
+    }
+}
+
+/// service which publishes the warnings
+pub trait WarningSource {
+    /// address of the warnings for the region
+    fn url(&self) -> String;
+
+    /// the warnings for the region, the most severe first
+    fn parse(&self, body: &[u8]) -> anyhow::Result<Vec<Warning>>;
+}
+
+/// warnings of the Deutscher Wetterdienst for one warn cell, the municipality or district of the DWD.
+/// Selected with the `WARNINGS` environment variable like `dwd:806412000`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DwdWarnings {
+    pub warn_cell: u32,
+}
+
+impl DwdWarnings {
+    pub fn from_code(code: &str) -> Option<Self> {
+        let warn_cell = code.trim().strip_prefix("dwd:")?.trim().parse().ok()?;
+        Some(Self { warn_cell })
+    }
+
+    /// warn cells of municipalities start with 8 or 9, the others are districts
+    fn layer(&self) -> &'static str {
+        match self.warn_cell / 100_000_000 {
+            8 | 9 => "Warnungen_Gemeinden",
+            _ => "Warnungen_Landkreise",
+        }
+    }
+}
+
+/// the feature collection of the DWD geoserver
+#[derive(Deserialize)]
+struct DwdCollection {
+    features: Vec<DwdFeature>,
+}
+
+#[derive(Deserialize)]
+struct DwdFeature {
+    properties: DwdProperties,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+struct DwdProperties {
+    warncellid: u32,
+    areadesc: String,
+    msgtype: String,
+    event: String,
+    severity: Severity,
+    headline: String,
+    onset: String,
+    expires: Option<String>,
+}
+
+impl WarningSource for DwdWarnings {
+    /// the geoserver filters the warnings by warn cell, the geometry is left out to keep the answer small
+    fn url(&self) -> String {
+        format!(
+            "https://maps.dwd.de/geoserver/dwd/ows?service=WFS&version=2.0.0&request=GetFeature&typeName=dwd:{}&outputFormat=application/json&propertyName=WARNCELLID,AREADESC,MSGTYPE,EVENT,SEVERITY,HEADLINE,ONSET,EXPIRES&CQL_FILTER=WARNCELLID%3D{}",
+            self.layer(),
+            self.warn_cell
+        )
+    }
+
+    fn parse(&self, body: &[u8]) -> anyhow::Result<Vec<Warning>> {
+        let collection = serde_json::from_slice::<DwdCollection>(body)?;
+        let time = |time: &str| DateTime::parse_from_rfc3339(time).map(|time| time.to_utc());
+        let mut warnings = (collection.features.into_iter())
+            .map(|feature| feature.properties)
+            .filter(|properties| {
+                properties.warncellid == self.warn_cell && properties.msgtype != "Cancel"
+            })
+            .map(|properties| {
+                Ok(Warning {
+                    onset: time(&properties.onset)?,
+                    expires: properties.expires.as_deref().map(time).transpose()?,
+                    headline: properties.headline,
+                    event: properties.event,
+                    severity: properties.severity,
+                    area: properties.areadesc,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        warnings.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.onset.cmp(&b.onset)));
+        Ok(warnings)
+    }
+}
+
+/// red banner with the most severe warning and the number of further warnings
+#[derive(Debug, Clone, Copy)]
+pub struct WarningBanner<'a> {
+    pub area: Area,
+    /// the most severe first
+    pub warnings: &'a [Warning],
+    /// timezone of the validity period
+    pub timezone: Tz,
+    pub language: Language,
+}
+
+impl WarningBanner<'_> {
+    /// severity and period like `Severe warning: Mar 5, 24 14:00 - Mar 6, 24 06:00 (+1)`
+    pub fn period(&self, warning: &Warning) -> String {
+        let time = |time: DateTime<Utc>| {
+            let time = time.with_timezone(&self.timezone).naive_local();
+            format!(
+                "{} {}",
+                self.language.format_date(time.date()),
+                time.format("%H:%M")
+            )
+        };
+        let expires = warning
+            .expires
+            .map_or_else(|| self.language.until_further_notice().to_string(), time);
+        let mut period = format!(
+            "{}: {} - {}",
+            warning.severity.name(self.language),
+            time(warning.onset),
+            expires
+        );
+        if self.warnings.len() > 1 {
+            period.push_str(&format!(" (+{})", self.warnings.len() - 1));
+        }
+        period
+    }
+
+    /// nothing is drawn without warnings
+    pub fn draw<Display>(&self, display: &mut Display)
+    where
+        Display: DrawTarget<Color = TriColor>,
+        Display::Error: std::fmt::Debug,
+    {
+        let Some(warning) = self.warnings.first() else {
+            return;
+        };
+        let area = self.area.rectangle();
+        area.into_styled(PrimitiveStyle::with_fill(TriColor::Chromatic))
+            .draw(display)
+            .unwrap();
+
+        // the headline takes what the period leaves
+        let inner = area.offset(-4);
+        let period_height = 12;
+        let headline = [
+            FontRenderer::new::<u8g2_font_helvB12_tf>(),
+            FontRenderer::new::<u8g2_font_helvB10_tf>(),
+        ];
+        let period = [FontRenderer::new::<u8g2_font_helvR08_tf>()];
+        TextBox {
+            area: Rectangle::new(
+                inner.top_left,
+                inner.size - Size::new(0, period_height.min(inner.size.height)),
+            ),
+            fonts: &headline,
+            alignment: HorizontalAlignment::Left,
+            middle: false,
+            color: TriColor::White,
+        }
+        .draw(display, &warning.headline);
+        TextBox {
+            area: Rectangle::new(
+                inner.top_left + Point::new(0, inner.size.height as i32 - period_height as i32),
+                Size::new(inner.size.width, period_height),
+            ),
+            fonts: &period,
+            alignment: HorizontalAlignment::Left,
+            middle: false,
+            color: TriColor::White,
+        }
+        .draw(display, &self.period(warning));
+    }
+}
@@ -0,0 +1,168 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+
+use super::{DateTime, Location, WeatherHourly};
+use crate::locale::Language;
+
+/// url of the open-meteo air quality forecast for the next three days, in the timezone of the weather forecast
+pub fn air_quality_url(location: &Location) -> String {
+    format!(
+        "https://air-quality-api.open-meteo.com/v1/air-quality?latitude={}&longitude={}&hourly=european_aqi,pm2_5,birch_pollen,grass_pollen,ragweed_pollen&timezone=auto&forecast_days=3",
+        location.latitude, location.longitude
+    )
+}
+
+/// the hourly air quality like open-meteo sends it. Pollen is only forecast in Europe, elsewhere and at the end
+/// of the forecast the values are `null`.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AirQualityForecast {
+    pub hourly: AirQualityHourly,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct AirQualityHourly {
+    pub time: Vec<DateTime>,
+    pub european_aqi: Vec<Option<f32>>,
+    /// fine particles in μg/m³
+    pub pm2_5: Vec<Option<f32>>,
+    /// pollen in grains/m³
+    pub birch_pollen: Vec<Option<f32>>,
+    pub grass_pollen: Vec<Option<f32>>,
+    pub ragweed_pollen: Vec<Option<f32>>,
+}
+
+impl AirQualityForecast {
+    /// the values of the hour starting at `time`, all `None` if the forecast does not cover it
+    pub fn at(&self, time: NaiveDateTime) -> AirQuality {
+        let hourly = &self.hourly;
+        let Some(index) = hourly.time.iter().position(|hour| hour.0 == time) else {
+            return AirQuality::default();
+        };
+        let value = |values: &[Option<f32>]| values.get(index).copied().flatten();
+        AirQuality {
+            european_aqi: value(&hourly.european_aqi),
+            pm2_5: value(&hourly.pm2_5),
+            birch_pollen: value(&hourly.birch_pollen),
+            grass_pollen: value(&hourly.grass_pollen),
+            ragweed_pollen: value(&hourly.ragweed_pollen),
+        }
+    }
+}
+
+/// air quality of one hour or the maximum of a day
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AirQuality {
+    pub european_aqi: Option<f32>,
+    pub pm2_5: Option<f32>,
+    pub birch_pollen: Option<f32>,
+    pub grass_pollen: Option<f32>,
+    pub ragweed_pollen: Option<f32>,
+}
+
+impl AirQuality {
+    /// european aqi from which the air is poor
+    pub const POOR: f32 = 60.0;
+
+    /// the highest value of each kind
+    pub fn max(self, other: Self) -> Self {
+        let max = |a: Option<f32>, b: Option<f32>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        Self {
+            european_aqi: max(self.european_aqi, other.european_aqi),
+            pm2_5: max(self.pm2_5, other.pm2_5),
+            birch_pollen: max(self.birch_pollen, other.birch_pollen),
+            grass_pollen: max(self.grass_pollen, other.grass_pollen),
+            ragweed_pollen: max(self.ragweed_pollen, other.ragweed_pollen),
+        }
+    }
+
+    /// the maximum of the hours of `date`, `None` without air quality for that day
+    pub fn daily_max(hourly: &WeatherHourly, date: NaiveDate) -> Option<Self> {
+        (hourly.time.iter())
+            .zip(&hourly.air_quality)
+            .filter(|(time, _)| time.0.date() == date)
+            .map(|(_, air_quality)| *air_quality)
+            .reduce(Self::max)
+            .filter(|air_quality| *air_quality != Self::default())
+    }
+
+    pub fn is_poor(&self) -> bool {
+        self.european_aqi.is_some_and(|aqi| aqi >= Self::POOR)
+    }
+
+    /// the pollen with their level, without the ones which are not in the air
+    pub fn pollen(&self) -> Vec<(Pollen, PollenLevel)> {
+        [
+            (Pollen::Birch, self.birch_pollen),
+            (Pollen::Grass, self.grass_pollen),
+            (Pollen::Ragweed, self.ragweed_pollen),
+        ]
+        .into_iter()
+        .filter_map(|(pollen, grains)| Some((pollen, pollen.level(grains?)?)))
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pollen {
+    Birch,
+    Grass,
+    Ragweed,
+}
+
+/// how much pollen is in the air
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PollenLevel {
+    Low,
+    Moderate,
+    High,
+}
+
+impl Pollen {
+    /// grains/m³ from which the level is moderate and high, birch pollen already troubles at lower counts
+    fn thresholds(&self) -> (f32, f32) {
+        match self {
+            Self::Birch => (10.0, 100.0),
+            Self::Grass => (10.0, 50.0),
+            Self::Ragweed => (5.0, 15.0),
+        }
+    }
+
+    /// `None` below one grain per m³
+    pub fn level(&self, grains: f32) -> Option<PollenLevel> {
+        let (moderate, high) = self.thresholds();
+        match grains {
+            grains if grains < 1.0 => None,
+            grains if grains < moderate => Some(PollenLevel::Low),
+            grains if grains < high => Some(PollenLevel::Moderate),
+            _ => Some(PollenLevel::High),
+        }
+    }
+
+    pub fn name(&self, language: Language) -> &'static str {
+        match (self, language) {
+            (Self::Birch, Language::English) => "Birch",
+            (Self::Birch, Language::German) => "Birke",
+            (Self::Grass, Language::English) => "Grass",
+            (Self::Grass, Language::German) => "Gräser",
+            (Self::Ragweed, Language::English) => "Ragweed",
+            (Self::Ragweed, Language::German) => "Ambrosia",
+        }
+    }
+}
+
+impl PollenLevel {
+    pub fn name(&self, language: Language) -> &'static str {
+        match (self, language) {
+            (Self::Low, Language::English) => "low",
+            (Self::Low, Language::German) => "gering",
+            (Self::Moderate, Language::English) => "moderate",
+            (Self::Moderate, Language::German) => "mittel",
+            (Self::High, Language::English) => "high",
+            (Self::High, Language::German) => "hoch",
+        }
+    }
+}
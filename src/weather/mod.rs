@@ -1,12 +1,84 @@
-use std::fmt::Debug;
-
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
 
 use embedded_graphics::{image::ImageDrawable, prelude::Point};
 use epd_waveshare::color::TriColor;
 use serde::Deserialize;
 
-use crate::{icons::convert_rgb565_to_binary, image_tri_color::ImageTriColor};
+pub mod air_quality;
+
+use self::air_quality::{AirQuality, AirQualityForecast};
+use crate::{
+    icons::convert_rgb565_to_binary,
+    image_tri_color::ImageTriColor,
+    locale::Language,
+    units::{
+        Factor, Precipitation, PrecipitationUnit, Speed, Temperature, TemperatureUnit, Units,
+        WindSpeedUnit,
+    },
+};
+
+/// coordinates of the forecast. Selected with the `LOCATION` environment variable as `latitude,longitude`,
+/// `latitude,longitude,air` additionally fetches the air quality and pollen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    pub latitude: f32,
+    pub longitude: f32,
+    pub air_quality: bool,
+}
+
+impl Default for Location {
+    fn default() -> Self {
+        // Frankfurt am Main
+        Self {
+            latitude: 50.1155,
+            longitude: 8.6842,
+            air_quality: false,
+        }
+    }
+}
+
+impl Location {
+    pub fn from_code(code: &str) -> Option<Self> {
+        let mut fields = code.split(',');
+        let location = Self {
+            latitude: fields.next()?.trim().parse().ok()?,
+            longitude: fields.next()?.trim().parse().ok()?,
+            air_quality: match fields.next().map(str::trim) {
+                Some("air") => true,
+                Some(_) => return None,
+                None => false,
+            },
+        };
+        if fields.next().is_some() {
+            return None;
+        }
+        ((-90.0..=90.0).contains(&location.latitude)
+            && (-180.0..=180.0).contains(&location.longitude))
+        .then_some(location)
+    }
+}
+
+/// the code parsed by [`Location::from_code`]
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}", self.latitude, self.longitude)?;
+        if self.air_quality {
+            write!(f, ",air")?;
+        }
+        Ok(())
+    }
+}
+
+/// url of the open-meteo forecast for the next seven days in the requested units, every page takes the days it shows.
+/// The timezone is derived from the location.
+pub fn forecast_url(location: &Location, units: &Units) -> String {
+    format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=temperature_2m,precipitation_probability,precipitation,weather_code,cloud_cover,wind_speed_10m&daily=sunrise,sunset,weather_code,temperature_2m_max,temperature_2m_min,precipitation_sum,uv_index_max,sunshine_duration,daylight_duration&timezone=auto&forecast_days=7&{}",
+        location.latitude,
+        location.longitude,
+        units.query()
+    )
+}
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(try_from = "String")]
@@ -41,13 +113,47 @@ impl TryFrom<String> for DateTime {
     }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(try_from = "u32")]
 pub struct WeatherCode(u32);
 impl WeatherCode {
     pub fn value(&self) -> u32 {
         self.0
     }
+    /// human readable condition of the WMO weather code
+    pub fn description(&self, language: Language) -> &'static str {
+        let (english, german) = match self.0 {
+            0 => ("Clear sky", "Klar"),
+            1 => ("Mainly clear", "Überwiegend klar"),
+            2 => ("Partly cloudy", "Teilweise bewölkt"),
+            3 => ("Overcast", "Bedeckt"),
+            45 => ("Fog", "Nebel"),
+            48 => ("Rime fog", "Raureifnebel"),
+            51 => ("Light drizzle", "Leichter Niesel"),
+            53 => ("Drizzle", "Nieselregen"),
+            55 => ("Dense drizzle", "Starker Niesel"),
+            56 | 57 => ("Freezing drizzle", "Gefrierender Niesel"),
+            61 => ("Light rain", "Leichter Regen"),
+            63 => ("Rain", "Regen"),
+            65 => ("Heavy rain", "Starker Regen"),
+            66 | 67 => ("Freezing rain", "Gefrierender Regen"),
+            71 => ("Light snow", "Leichter Schnee"),
+            73 => ("Snow", "Schnee"),
+            75 => ("Heavy snow", "Starker Schnee"),
+            77 => ("Snow grains", "Schneegriesel"),
+            80 => ("Light showers", "Leichte Schauer"),
+            81 => ("Showers", "Schauer"),
+            82 => ("Violent showers", "Heftige Schauer"),
+            85 | 86 => ("Snow showers", "Schneeschauer"),
+            95 => ("Thunderstorm", "Gewitter"),
+            96 | 99 => ("Thunderstorm with hail", "Gewitter mit Hagel"),
+            _ => ("Unknown", "Unbekannt"),
+        };
+        match language {
+            Language::English => english,
+            Language::German => german,
+        }
+    }
     pub fn to_clouded_icon(&self) -> (Vec<u8>, u32) {
         let image = match self.0 {
             3 | 4 => embedded_weather_icons::wi_cloudy_32x32(),
@@ -109,13 +215,13 @@ impl WeatherCode {
         display: &mut Display,
         x: i32,
         y: i32,
-        cloud_coverage: u32,
+        cloud_coverage: Factor,
         is_day: bool,
     ) where
         Display: embedded_graphics::draw_target::DrawTarget<Color = TriColor>,
         <Display as embedded_graphics::draw_target::DrawTarget>::Error: std::fmt::Debug,
     {
-        let (icon, width) = if cloud_coverage > 80 {
+        let (icon, width) = if cloud_coverage.value() > 80.0 {
             self.to_clouded_icon()
         } else if is_day {
             self.to_day_icon()
@@ -142,73 +248,181 @@ impl TryFrom<u32> for WeatherCode {
     }
 }
 
-#[derive(Deserialize, Clone)]
-#[serde(try_from = "String")]
-pub struct Factor(f32);
-
-impl Factor {
-    pub fn value(&self) -> f32 {
-        self.0
-    }
+#[derive(Deserialize, Debug, Default)]
+#[serde(from = "RawWeatherForecast")]
+pub struct WeatherForecast {
+    pub utc_offset_seconds: i32,
+    pub timezone: String,
+    pub timezone_abbreviation: String,
+    pub hourly_units: WeatherHourlyUnits,
+    pub hourly: WeatherHourly,
+    pub daily: WeatherDaily,
 }
 
-impl TryFrom<String> for Factor {
-    type Error = anyhow::Error;
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        Ok(Self(value.parse::<f32>()?))
-    }
+#[derive(Debug, Clone, Default)]
+pub struct WeatherHourly {
+    pub time: Vec<DateTime>,
+    pub temperature_2m: Vec<Temperature>,
+    pub precipitation_probability: Vec<Factor>,
+    pub precipitation: Vec<Precipitation>,
+    pub weather_code: Vec<WeatherCode>,
+    pub cloud_cover: Vec<Factor>,
+    pub wind_speed_10m: Vec<Speed>,
+    /// one entry per hour after [`WeatherForecast::merge_air_quality`], empty without air quality
+    pub air_quality: Vec<AirQuality>,
 }
 
-impl Debug for Factor {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(format!("{:.0} %", self.0).as_str())
-    }
+/// units open-meteo used for the values in [`WeatherHourly`]
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+pub struct WeatherHourlyUnits {
+    pub temperature_2m: TemperatureUnit,
+    pub precipitation: PrecipitationUnit,
+    pub wind_speed_10m: WindSpeedUnit,
 }
 
-#[derive(Deserialize, Clone)]
-#[serde(try_from = "String")]
-pub struct Millimeter(f32);
+/// the forecast like open-meteo sends it. The units are only known from `hourly_units`, so the plain numbers are combined with them afterwards.
+#[derive(Deserialize)]
+struct RawWeatherForecast {
+    utc_offset_seconds: i32,
+    timezone: String,
+    timezone_abbreviation: String,
+    hourly_units: WeatherHourlyUnits,
+    hourly: RawWeatherHourly,
+    daily: RawWeatherDaily,
+}
 
-impl Millimeter {
-    pub fn value(&self) -> f32 {
-        self.0
-    }
+#[derive(Deserialize)]
+struct RawWeatherHourly {
+    time: Vec<DateTime>,
+    temperature_2m: Vec<f32>,
+    precipitation_probability: Vec<Factor>,
+    precipitation: Vec<f32>,
+    weather_code: Vec<WeatherCode>,
+    cloud_cover: Vec<Factor>,
+    wind_speed_10m: Vec<f32>,
 }
 
-impl TryFrom<String> for Millimeter {
-    type Error = anyhow::Error;
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        Ok(Self(value.parse::<f32>()?))
-    }
+#[derive(Deserialize)]
+struct RawWeatherDaily {
+    time: Vec<Date>,
+    sunrise: Vec<DateTime>,
+    sunset: Vec<DateTime>,
+    weather_code: Vec<WeatherCode>,
+    temperature_2m_max: Vec<f32>,
+    temperature_2m_min: Vec<f32>,
+    precipitation_sum: Vec<f32>,
+    uv_index_max: Vec<f32>,
+    /// seconds
+    sunshine_duration: Vec<f32>,
+    /// seconds
+    daylight_duration: Vec<f32>,
 }
 
-impl Debug for Millimeter {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(format!("{:.2} mm", self.0).as_str())
+impl From<RawWeatherForecast> for WeatherForecast {
+    fn from(raw: RawWeatherForecast) -> Self {
+        let units = raw.hourly_units;
+        let hourly = WeatherHourly {
+            time: raw.hourly.time,
+            temperature_2m: (raw.hourly.temperature_2m.into_iter())
+                .map(|value| Temperature::new(value, units.temperature_2m))
+                .collect(),
+            precipitation_probability: raw.hourly.precipitation_probability,
+            precipitation: (raw.hourly.precipitation.into_iter())
+                .map(|value| Precipitation::new(value, units.precipitation))
+                .collect(),
+            weather_code: raw.hourly.weather_code,
+            cloud_cover: raw.hourly.cloud_cover,
+            wind_speed_10m: (raw.hourly.wind_speed_10m.into_iter())
+                .map(|value| Speed::new(value, units.wind_speed_10m))
+                .collect(),
+            air_quality: Vec::new(),
+        };
+        // the unit parameters of the request apply to the daily values as well
+        let temperatures = |values: Vec<f32>| {
+            (values.into_iter())
+                .map(|value| Temperature::new(value, units.temperature_2m))
+                .collect()
+        };
+        let durations = |seconds: Vec<f32>| {
+            (seconds.into_iter())
+                .map(|seconds| TimeDelta::seconds(seconds as i64))
+                .collect()
+        };
+        let daily = WeatherDaily {
+            time: raw.daily.time,
+            sunrise: raw.daily.sunrise,
+            sunset: raw.daily.sunset,
+            weather_code: raw.daily.weather_code,
+            temperature_2m_max: temperatures(raw.daily.temperature_2m_max),
+            temperature_2m_min: temperatures(raw.daily.temperature_2m_min),
+            precipitation_sum: (raw.daily.precipitation_sum.into_iter())
+                .map(|value| Precipitation::new(value, units.precipitation))
+                .collect(),
+            uv_index_max: raw.daily.uv_index_max,
+            sunshine_duration: durations(raw.daily.sunshine_duration),
+            daylight_duration: durations(raw.daily.daylight_duration),
+        };
+        Self {
+            utc_offset_seconds: raw.utc_offset_seconds,
+            timezone: raw.timezone,
+            timezone_abbreviation: raw.timezone_abbreviation,
+            hourly_units: units,
+            hourly,
+            daily,
+        }
     }
 }
 
-#[derive(Deserialize, Debug, Default)]
-pub struct WeatherForecast {
-    pub utc_offset_seconds: u32,
-    pub timezone: String,
-    pub timezone_abbreviation: String,
-    pub hourly: WeatherHourly,
-    pub daily: WeatherDaily,
+impl WeatherForecast {
+    /// put the air quality onto the hours of the forecast, hours it does not cover have no values
+    pub fn merge_air_quality(&mut self, air_quality: &AirQualityForecast) {
+        self.hourly.air_quality = (self.hourly.time.iter())
+            .map(|time| air_quality.at(time.0))
+            .collect();
+    }
 }
 
-#[derive(Deserialize, Debug, Clone, Default)]
-pub struct WeatherHourly {
-    pub time: Vec<DateTime>,
-    pub temperature_2m: Vec<f32>,
-    pub precipitation_probability: Vec<u32>,
-    pub precipitation: Vec<f32>,
-    pub weather_code: Vec<WeatherCode>,
-    pub cloud_cover: Vec<u32>,
-}
-#[derive(Deserialize, Debug, Clone, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct WeatherDaily {
     pub time: Vec<Date>,
     pub sunrise: Vec<DateTime>,
     pub sunset: Vec<DateTime>,
+    /// the most severe condition of the day
+    pub weather_code: Vec<WeatherCode>,
+    pub temperature_2m_max: Vec<Temperature>,
+    pub temperature_2m_min: Vec<Temperature>,
+    pub precipitation_sum: Vec<Precipitation>,
+    /// the strongest ultraviolet radiation of the day
+    pub uv_index_max: Vec<f32>,
+    /// time with direct sunlight
+    pub sunshine_duration: Vec<TimeDelta>,
+    /// time from sunrise to sunset
+    pub daylight_duration: Vec<TimeDelta>,
+}
+
+impl WeatherDaily {
+    /// UV index from which the skin needs protection, high on the scale of the WHO
+    pub const UV_HIGH: f32 = 6.0;
+
+    /// share of the daylight with sunshine of day `index` from 0 to 1
+    pub fn sunshine_ratio(&self, index: usize) -> Option<f32> {
+        let sunshine = self.sunshine_duration.get(index)?;
+        let daylight = self.daylight_duration.get(index)?;
+        (daylight.num_seconds() > 0).then(|| {
+            (sunshine.num_seconds() as f32 / daylight.num_seconds() as f32).clamp(0.0, 1.0)
+        })
+    }
+
+    /// maximal UV index of day `index`, rounded to the whole number which is shown and compared with [`Self::UV_HIGH`]
+    pub fn uv_index(&self, index: usize) -> Option<f32> {
+        self.uv_index_max
+            .get(index)
+            .map(|uv_index| uv_index.round())
+    }
+
+    /// hours of sunshine of day `index`
+    pub fn sunshine_hours(&self, index: usize) -> Option<f32> {
+        let sunshine = self.sunshine_duration.get(index)?;
+        Some(sunshine.num_seconds() as f32 / 3600.0)
+    }
 }
@@ -1,84 +1,274 @@
-use esp_idf_svc::sys::{esp, EspError};
+use std::time::{Duration, Instant};
+
+use esp_idf_svc::sys::esp;
 use esp_idf_svc::{
     eventloop::EspSystemEventLoop,
     handle::RawHandle,
-    ipv4::IpInfo,
-    wifi::{AccessPointConfiguration, BlockingWifi, ClientConfiguration, Configuration, EspWifi},
+    ipv4::{self, IpInfo},
+    netif::{EspNetif, NetifConfiguration},
+    wifi::{
+        AccessPointConfiguration, AuthMethod, BlockingWifi, ClientConfiguration, Configuration,
+        EspWifi,
+    },
+};
+
+use crate::{
+    config::AccessPoint,
+    network::{
+        connection_order, Candidate, KnownNetwork, LastConnection, Lease, NetworkStatus, ScanResult,
+    },
 };
 
-/// connect to wifi. This is just copied from the espressif example. Modify the `PASSWORD` and `SSID` to connect to your wifi. Could be changed to run as access point.
-pub fn wifi(
-    modem: esp_idf_svc::hal::modem::WifiModem,
+/// access point of the last wake cycle. The rtc memory keeps it during the deep sleep, it is cleared by a reset.
+#[link_section = ".rtc.data"]
+static mut LAST_CONNECTION: Option<LastConnection> = None;
+
+/// connect to wifi. This is just copied from the espressif example. The access point of the last wake cycle is tried
+/// first without scan, with `reuse_lease` also with its last ip address instead of dhcp. Otherwise the known networks
+/// found by the scan are tried by priority and signal strength, each with its own `timeout`.
+/// By default only the client is started, with `access_point` the device additionally opens a WPA2 protected access point.
+pub fn connect(
+    esp_wifi: &mut EspWifi<'static>,
     sysloop: EspSystemEventLoop,
-    ssid: &str,
-    password: &str,
-) -> anyhow::Result<(Box<EspWifi<'static>>, Option<IpInfo>)> {
-    let mut esp_wifi = EspWifi::new(modem, sysloop.clone(), None)?;
+    networks: &[KnownNetwork],
+    timeout: Duration,
+    access_point: Option<&AccessPoint>,
+    reuse_lease: bool,
+) -> anyhow::Result<NetworkStatus> {
+    if access_point.is_some_and(|access_point| access_point.password.is_none()) {
+        anyhow::bail!("refusing to open an access point without password");
+    }
 
-    let mut wifi = BlockingWifi::wrap(&mut esp_wifi, sysloop)?;
+    let started = Instant::now();
+    let mut wifi = BlockingWifi::wrap(&mut *esp_wifi, sysloop)?;
+    if !wifi.is_started()? {
+        wifi.set_configuration(&Configuration::Client(ClientConfiguration::default()))?;
 
-    wifi.set_configuration(&Configuration::Client(ClientConfiguration::default()))?;
+        log::info!("Starting wifi...");
 
-    log::info!("Starting wifi...");
+        wifi.start()?;
+    }
 
-    wifi.start()?;
+    let last_connection = unsafe { LAST_CONNECTION };
+    if let Some(candidate) = last_connection.and_then(|last| last.candidate(networks)) {
+        let lease = last_connection
+            .and_then(|last| last.lease)
+            .filter(|_| reuse_lease);
+        let result = set_lease(wifi.wifi_mut(), lease.as_ref())
+            .and_then(|_| connect_candidate(wifi.wifi_mut(), &candidate, timeout, access_point));
+        match result {
+            Ok(ip_info) => {
+                log::info!(
+                    "Reconnected to the cached access point in {} ms",
+                    started.elapsed().as_millis()
+                );
+                return remember(candidate.network, ip_info);
+            }
+            Err(err) => {
+                log::warn!("Cached access point failed, scanning: {err}");
+                unsafe { LAST_CONNECTION = None };
+                if lease.is_some() {
+                    reset_lease(wifi.wifi_mut())?;
+                }
+            }
+        }
+    }
 
     log::info!("Scanning...");
 
-    let ap_infos = wifi.scan()?;
+    let scan = wifi
+        .scan()?
+        .into_iter()
+        .map(|info| ScanResult {
+            ssid: info.ssid.to_string(),
+            signal: info.signal_strength,
+            channel: info.channel,
+        })
+        .collect::<Vec<_>>();
+
+    for candidate in connection_order(networks, &scan) {
+        match connect_candidate(wifi.wifi_mut(), &candidate, timeout, access_point) {
+            Ok(ip_info) => {
+                log::info!(
+                    "Connected after a full scan in {} ms",
+                    started.elapsed().as_millis()
+                );
+                return remember(candidate.network, ip_info);
+            }
+            Err(err) => log::warn!("Could not connect to {}: {err}", candidate.network.ssid),
+        }
+    }
+
+    anyhow::bail!("none of the {} known networks is reachable", networks.len())
+}
+
+/// network of the last wake cycle, the wifi is off between the forecasts
+pub fn last_network() -> Option<NetworkStatus> {
+    let last = unsafe { LAST_CONNECTION }?;
+    Some(NetworkStatus {
+        ssid: last.ssid().to_string(),
+        signal: None,
+        ip: last.lease.map(|lease| lease.ip),
+    })
+}
+
+/// store the access point in the rtc memory for the next wake cycle
+fn remember(network: &KnownNetwork, ip_info: Option<IpInfo>) -> anyhow::Result<NetworkStatus> {
+    let mut record = esp_idf_svc::sys::wifi_ap_record_t::default();
+    esp!(unsafe { esp_idf_svc::sys::esp_wifi_sta_get_ap_info(&mut record) })?;
+
+    let lease = ip_info.as_ref().map(|ip_info| Lease {
+        ip: ip_info.ip,
+        gateway: ip_info.subnet.gateway,
+        prefix: ip_info.subnet.mask.0,
+        dns: ip_info.dns,
+    });
+    unsafe {
+        LAST_CONNECTION = LastConnection::new(&network.ssid, record.bssid, record.primary, lease);
+    }
+
+    Ok(NetworkStatus {
+        ssid: network.ssid.clone(),
+        signal: Some(record.rssi),
+        ip: ip_info.map(|ip_info| ip_info.ip),
+    })
+}
+
+/// use the fixed `lease` for the station interface. Without a lease the default dhcp netif is kept untouched.
+fn set_lease(wifi: &mut EspWifi<'static>, lease: Option<&Lease>) -> anyhow::Result<()> {
+    let Some(lease) = lease else {
+        return Ok(());
+    };
+    log::info!("Reusing the ip address {}", lease.ip);
+    swap_netif(
+        wifi,
+        ipv4::ClientConfiguration::Fixed(ipv4::ClientSettings {
+            ip: lease.ip,
+            subnet: ipv4::Subnet {
+                gateway: lease.gateway,
+                mask: ipv4::Mask(lease.prefix),
+            },
+            dns: lease.dns,
+            secondary_dns: None,
+        }),
+    )
+}
+
+/// go back to dhcp after the fixed lease failed
+fn reset_lease(wifi: &mut EspWifi<'static>) -> anyhow::Result<()> {
+    swap_netif(wifi, ipv4::ClientConfiguration::DHCP(Default::default()))
+}
 
-    let ours = ap_infos.into_iter().find(|a| a.ssid == ssid);
+fn swap_netif(
+    wifi: &mut EspWifi<'static>,
+    ip_configuration: ipv4::ClientConfiguration,
+) -> anyhow::Result<()> {
+    let netif = EspNetif::new_with_conf(&NetifConfiguration {
+        ip_configuration: Some(ipv4::Configuration::Client(ip_configuration)),
+        ..NetifConfiguration::wifi_default_client()
+    })?;
+    wifi.swap_netif_sta(netif)?;
+    Ok(())
+}
 
-    let channel = if let Some(ours) = ours {
-        log::info!(
+fn connect_candidate(
+    wifi: &mut EspWifi<'static>,
+    candidate: &Candidate,
+    timeout: Duration,
+    access_point: Option<&AccessPoint>,
+) -> anyhow::Result<Option<IpInfo>> {
+    let ssid = &candidate.network.ssid;
+    let password = &candidate.network.password;
+    match candidate.channel {
+        Some(channel) => log::info!(
             "Found configured access point {} on channel {}",
             ssid,
-            ours.channel
-        );
-        Some(ours.channel)
-    } else {
-        log::info!(
-            "Configured access point {} not found during scanning, will go with unknown channel",
+            channel
+        ),
+        None => log::warn!(
+            "Configured access point {} not found during scanning, trying it as hidden network",
             ssid
-        );
-        None
+        ),
+    }
+
+    let client = ClientConfiguration {
+        ssid: ssid
+            .as_str()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("network name {ssid} is too long"))?,
+        password: password
+            .expose()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("password {password} is too long"))?,
+        channel: candidate.channel,
+        bssid: candidate.bssid,
+        ..Default::default()
     };
 
-    wifi.set_configuration(&Configuration::Mixed(
-        ClientConfiguration {
-            ssid: ssid.try_into().unwrap(),
-            password: password.try_into().unwrap(),
-            channel,
-            ..Default::default()
-        },
-        AccessPointConfiguration {
-            ssid: "aptest".try_into().unwrap(),
-            channel: channel.unwrap_or(1),
-            ..Default::default()
-        },
-    ))?;
-
-    let ip_info = {
-        log::info!("Connecting wifi...");
-        wifi.connect()?;
-
-        log::info!("Waiting for DHCP lease...");
-        wifi.wait_netif_up()?;
-
-        if esp!(unsafe {
-            esp_idf_svc::sys::esp_netif_create_ip6_linklocal(wifi.wifi().sta_netif().handle())
-        })
-        .is_err()
-        {
-            log::error!("failed to create iplink local address")
+    match access_point {
+        Some(access_point) => {
+            log::info!("Opening access point {}", access_point.ssid);
+            wifi.set_configuration(&Configuration::Mixed(
+                client,
+                access_point_configuration(access_point, candidate.channel.unwrap_or(1))?,
+            ))?
+        }
+        None => wifi.set_configuration(&Configuration::Client(client))?,
+    }
+
+    log::info!("Connecting wifi...");
+    wifi.connect()?;
+
+    log::info!("Waiting for DHCP lease...");
+    let deadline = Instant::now() + timeout;
+    while !wifi.is_up()? {
+        if Instant::now() > deadline {
+            let _ = wifi.disconnect();
+            anyhow::bail!("no connection after {} seconds", timeout.as_secs());
         }
-        let ip_info = wifi.wifi().sta_netif().get_ip_info()?;
+        std::thread::sleep(Duration::from_millis(100));
+    }
 
-        log::info!("Wifi DHCP info: {:?}", ip_info);
-        Ok::<_, EspError>(ip_info)
-    };
+    if esp!(unsafe { esp_idf_svc::sys::esp_netif_create_ip6_linklocal(wifi.sta_netif().handle()) })
+        .is_err()
+    {
+        log::error!("failed to create iplink local address")
+    }
+    let ip_info = wifi.sta_netif().get_ip_info().ok();
+
+    log::info!("Wifi DHCP info: {:?}", ip_info);
+    Ok(ip_info)
+}
 
-    Ok((Box::new(esp_wifi), ip_info.ok()))
+/// WPA2 protected access point, or an open one if it has no password
+pub fn access_point_configuration(
+    access_point: &AccessPoint,
+    channel: u8,
+) -> anyhow::Result<AccessPointConfiguration> {
+    let (auth_method, password) = match &access_point.password {
+        Some(password) if password.len() < 8 => {
+            anyhow::bail!("access point password needs at least 8 characters for WPA2")
+        }
+        Some(password) => (
+            AuthMethod::WPA2Personal,
+            password
+                .expose()
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("access point password {password} is too long"))?,
+        ),
+        None => (AuthMethod::None, Default::default()),
+    };
+    Ok(AccessPointConfiguration {
+        ssid: access_point
+            .ssid
+            .as_str()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("access point name {} is too long", access_point.ssid))?,
+        auth_method,
+        password,
+        channel,
+        ..Default::default()
+    })
 }
 
 pub fn ping(ip: esp_idf_svc::ipv4::Ipv4Addr) -> anyhow::Result<()> {
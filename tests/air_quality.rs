@@ -0,0 +1,94 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use esp_weather::weather::{
+    air_quality::{AirQuality, AirQualityForecast, Pollen, PollenLevel},
+    DateTime, Location, WeatherForecast,
+};
+
+const AIR_QUALITY: &str = include_str!("fixtures/air_quality.json");
+
+fn hour(day: u32, hour: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 3, day)
+        .unwrap()
+        .and_hms_opt(hour, 0, 0)
+        .unwrap()
+}
+
+/// a forecast which starts an hour before the air quality
+fn forecast() -> WeatherForecast {
+    let mut forecast = WeatherForecast::default();
+    forecast.hourly.time = [
+        hour(5, 21),
+        hour(5, 22),
+        hour(5, 23),
+        hour(6, 0),
+        hour(6, 1),
+    ]
+    .into_iter()
+    .map(DateTime)
+    .collect();
+    forecast
+}
+
+#[test]
+fn parses_locations_with_air_quality() {
+    let location = Location::from_code("50.11, 8.68,air").unwrap();
+    assert!(location.air_quality);
+    assert_eq!(location.to_string(), "50.11,8.68,air");
+    assert!(!Location::from_code("50.11,8.68").unwrap().air_quality);
+    assert_eq!(Location::from_code("50.11,8.68,pollen"), None);
+    assert_eq!(Location::from_code("50.11,8.68,air,air"), None);
+}
+
+#[test]
+fn merges_onto_the_hours_of_the_forecast() {
+    let air_quality = serde_json::from_str::<AirQualityForecast>(AIR_QUALITY).unwrap();
+    let mut forecast = forecast();
+    forecast.merge_air_quality(&air_quality);
+
+    let hourly = &forecast.hourly;
+    assert_eq!(hourly.air_quality.len(), hourly.time.len());
+    assert_eq!(hourly.air_quality[0], AirQuality::default());
+    assert_eq!(
+        hourly.air_quality[2],
+        AirQuality {
+            european_aqi: Some(62.0),
+            pm2_5: Some(21.7),
+            birch_pollen: Some(120.5),
+            grass_pollen: Some(0.2),
+            ragweed_pollen: None,
+        }
+    );
+    assert_eq!(hourly.air_quality[4], AirQuality::default());
+}
+
+#[test]
+fn summarizes_the_days() {
+    let air_quality = serde_json::from_str::<AirQualityForecast>(AIR_QUALITY).unwrap();
+    let mut forecast = forecast();
+    forecast.merge_air_quality(&air_quality);
+
+    let evening = AirQuality::daily_max(&forecast.hourly, hour(5, 0).date()).unwrap();
+    assert_eq!(evening.european_aqi, Some(62.0));
+    assert!(evening.is_poor());
+    assert_eq!(evening.pollen(), vec![(Pollen::Birch, PollenLevel::High)]);
+
+    let night = AirQuality::daily_max(&forecast.hourly, hour(6, 0).date()).unwrap();
+    assert!(!night.is_poor());
+    assert_eq!(
+        night.pollen(),
+        vec![
+            (Pollen::Birch, PollenLevel::Moderate),
+            (Pollen::Grass, PollenLevel::Moderate)
+        ]
+    );
+
+    // no values for the day at all
+    assert_eq!(
+        AirQuality::daily_max(&forecast.hourly, hour(7, 0).date()),
+        None
+    );
+    assert_eq!(
+        AirQuality::daily_max(&WeatherForecast::default().hourly, hour(5, 0).date()),
+        None
+    );
+}
@@ -0,0 +1,89 @@
+use esp_weather::board::{Board, BoardError, Chip};
+
+const ESP32C6: Chip = Chip {
+    gpio_count: 31,
+    spi3: false,
+    rtc_pins: 0xff,
+};
+const ESP32: Chip = Chip {
+    gpio_count: 40,
+    spi3: true,
+    rtc_pins: 0xff_0e00_f015,
+};
+
+#[test]
+fn presets_with_overrides() {
+    assert_eq!(Board::from_code("hat"), Ok(Board::hat()));
+    let board = Board::from_code("driver-board, busy=4, power=5, power_active=low").unwrap();
+    assert_eq!(board.busy, 4);
+    assert_eq!(board.power, Some(5));
+    assert!(!board.power_active_high);
+    assert_eq!(board.sclk, Board::driver_board().sclk);
+    assert_eq!(Board::from_code("power=none").unwrap().power, None);
+}
+
+#[test]
+fn rejects_invalid_settings() {
+    assert_eq!(
+        Board::from_code("hat,miso=4"),
+        Err(BoardError::UnknownSetting("miso".to_string()))
+    );
+    assert_eq!(
+        Board::from_code("busy=four"),
+        Err(BoardError::InvalidValue("busy".to_string()))
+    );
+    assert_eq!(
+        Board::from_code("lolin"),
+        Err(BoardError::UnknownSetting("lolin".to_string()))
+    );
+}
+
+#[test]
+fn validates_pins() {
+    assert_eq!(Board::hat().validate(&ESP32C6), Ok(()));
+    assert_eq!(
+        Board::driver_board().validate(&Chip {
+            gpio_count: 22,
+            ..ESP32
+        }),
+        Err(BoardError::InvalidPin(25))
+    );
+    assert_eq!(
+        Board::from_code("dc=1").unwrap().validate(&ESP32C6),
+        Err(BoardError::DuplicatePin(1))
+    );
+    assert_eq!(
+        Board::from_code("host=1").unwrap().validate(&ESP32C6),
+        Err(BoardError::InvalidHost(1))
+    );
+    assert_eq!(
+        Board::from_code("host=3").unwrap().validate(&ESP32C6),
+        Err(BoardError::InvalidHost(3))
+    );
+    assert_eq!(
+        Board::from_code("driver-board,host=3")
+            .unwrap()
+            .validate(&ESP32),
+        Ok(())
+    );
+    assert_eq!(
+        Board::from_code("hat,button=4").unwrap().validate(&ESP32C6),
+        Ok(())
+    );
+    assert_eq!(
+        Board::from_code("button=9").unwrap().validate(&ESP32C6),
+        Err(BoardError::NoWakePin(9))
+    );
+    assert_eq!(
+        Board::from_code("driver-board,button=5")
+            .unwrap()
+            .validate(&ESP32),
+        Err(BoardError::NoWakePin(5))
+    );
+    assert_eq!(
+        Board::from_code("frequency=50000000")
+            .unwrap()
+            .validate(&ESP32C6),
+        Err(BoardError::InvalidFrequency(50_000_000))
+    );
+}
@@ -0,0 +1,183 @@
+use embedded_graphics::{
+    mock_display::MockDisplay,
+    prelude::*,
+    primitives::{Line, Rectangle},
+};
+use epd_waveshare::color::TriColor;
+use esp_weather::chart::{
+    draw_dashed, peaks, AreaSeries, BarSeries, Chart, Dash, Legend, LineSeries, TimeAxis, ValueAxis,
+};
+
+fn display() -> MockDisplay<TriColor> {
+    let mut display = MockDisplay::new();
+    display.set_allow_overdraw(true);
+    display
+}
+
+/// 64 x 64 chart with a sample every 10 pixels and the values 0 to 63 mapped to the rows
+fn chart() -> Chart {
+    Chart {
+        area: Rectangle::new(Point::zero(), Size::new(64, 64)),
+        step: 10,
+        min: 0.0,
+        max: 63.0,
+    }
+}
+
+#[test]
+fn maps_samples_into_the_area() {
+    let chart = chart();
+    assert_eq!(chart.point(0, 0.0), Point::new(0, 63));
+    assert_eq!(chart.point(3, 63.0), Point::new(30, 0));
+    assert_eq!(chart.y(31.0), 32);
+    // the seventh sample is on the last column
+    assert_eq!(chart.samples(), 7);
+    // a day of 33 pixel columns starting half a column from the edge of a 800 pixel panel
+    let day = Chart {
+        area: Rectangle::with_corners(Point::new(16, 0), Point::new(799, 10)),
+        step: 33,
+        ..chart
+    };
+    assert_eq!(day.samples(), 24);
+}
+
+#[test]
+fn peaks_of_each_run() {
+    assert_eq!(
+        peaks([0.0, 0.2, 0.8, 0.5, 0.0, 0.0, 0.1, 0.4, 0.4], 0.0),
+        [2, 7]
+    );
+    assert!(peaks([0.0, 0.0], 0.0).is_empty());
+}
+
+#[test]
+fn line_starts_at_the_origin() {
+    let mut display = display();
+    // the first point is (0, 0), which was used as the start marker before
+    LineSeries::new(TriColor::Black, 1).draw(&mut display, &chart(), [63.0, 63.0, 53.0]);
+    assert_eq!(display.get_pixel(Point::new(0, 0)), Some(TriColor::Black));
+    assert_eq!(display.get_pixel(Point::new(5, 0)), Some(TriColor::Black));
+    assert_eq!(display.get_pixel(Point::new(20, 10)), Some(TriColor::Black));
+    assert_eq!(display.get_pixel(Point::new(0, 1)), None);
+}
+
+#[test]
+fn bars_grow_from_the_bottom() {
+    let mut display = display();
+    BarSeries {
+        color: TriColor::Chromatic,
+        width: 4,
+    }
+    .draw(&mut display, &chart(), [0.0, 10.0]);
+    // nothing for the empty sample
+    assert_eq!(display.get_pixel(Point::new(0, 63)), None);
+    assert_eq!(
+        display.get_pixel(Point::new(8, 63)),
+        Some(TriColor::Chromatic)
+    );
+    assert_eq!(
+        display.get_pixel(Point::new(11, 53)),
+        Some(TriColor::Chromatic)
+    );
+    assert_eq!(display.get_pixel(Point::new(12, 63)), None);
+    assert_eq!(display.get_pixel(Point::new(10, 52)), None);
+}
+
+#[test]
+fn area_is_filled_below_the_line() {
+    let mut display = display();
+    AreaSeries {
+        color: TriColor::Black,
+    }
+    .draw(&mut display, &chart(), [63.0, 53.0]);
+    assert_eq!(display.get_pixel(Point::new(0, 0)), Some(TriColor::Black));
+    assert_eq!(display.get_pixel(Point::new(0, 63)), Some(TriColor::Black));
+    assert_eq!(display.get_pixel(Point::new(5, 4)), None);
+    assert_eq!(display.get_pixel(Point::new(5, 5)), Some(TriColor::Black));
+    assert_eq!(display.get_pixel(Point::new(10, 10)), Some(TriColor::Black));
+    assert_eq!(display.get_pixel(Point::new(11, 63)), None);
+}
+
+#[test]
+fn dashes_continue_over_segments() {
+    let dash = Dash { on: 2, off: 2 };
+    let mut display = display();
+    let mut n = 0;
+    draw_dashed(
+        &mut display,
+        Line::new(Point::new(0, 0), Point::new(2, 0)),
+        TriColor::Black,
+        dash,
+        &mut n,
+    );
+    draw_dashed(
+        &mut display,
+        Line::new(Point::new(3, 0), Point::new(7, 0)),
+        TriColor::Black,
+        dash,
+        &mut n,
+    );
+    let row = (0..8)
+        .map(|x| display.get_pixel(Point::new(x, 0)).is_some())
+        .collect::<Vec<_>>();
+    assert_eq!(row, [true, true, false, false, true, true, false, false]);
+}
+
+#[test]
+fn axes_with_ticks_and_gridlines() {
+    let mut display = display();
+    display.set_allow_out_of_bounds_drawing(true);
+    let chart = Chart {
+        area: Rectangle::new(Point::new(20, 0), Size::new(40, 40)),
+        ..chart()
+    };
+    TimeAxis {
+        color: TriColor::Black,
+        tick: 3,
+    }
+    .draw(&mut display, &chart, [(1, "")]);
+    // the line below the chart and the tick below the second sample
+    assert_eq!(display.get_pixel(Point::new(20, 40)), Some(TriColor::Black));
+    assert_eq!(display.get_pixel(Point::new(59, 40)), Some(TriColor::Black));
+    assert_eq!(display.get_pixel(Point::new(30, 43)), Some(TriColor::Black));
+    assert_eq!(display.get_pixel(Point::new(31, 43)), None);
+
+    ValueAxis {
+        color: TriColor::Chromatic,
+        tick: 2,
+        grid: Some(Dash { on: 1, off: 1 }),
+    }
+    .draw(&mut display, &chart, [(0.0, "")]);
+    assert_eq!(
+        display.get_pixel(Point::new(19, 0)),
+        Some(TriColor::Chromatic)
+    );
+    assert_eq!(
+        display.get_pixel(Point::new(17, 39)),
+        Some(TriColor::Chromatic)
+    );
+    assert_eq!(
+        display.get_pixel(Point::new(20, 39)),
+        Some(TriColor::Chromatic)
+    );
+    assert_eq!(display.get_pixel(Point::new(21, 39)), None);
+}
+
+#[test]
+fn legend_swatches() {
+    let mut display = display();
+    display.set_allow_out_of_bounds_drawing(true);
+    Legend {
+        entries: &[("rain", TriColor::Chromatic)],
+    }
+    .draw(&mut display, Point::new(1, 1));
+    assert_eq!(
+        display.get_pixel(Point::new(1, 1)),
+        Some(TriColor::Chromatic)
+    );
+    assert_eq!(
+        display.get_pixel(Point::new(8, 8)),
+        Some(TriColor::Chromatic)
+    );
+    assert_eq!(display.get_pixel(Point::new(9, 1)), None);
+}
@@ -0,0 +1,24 @@
+use chrono::{TimeZone, Utc};
+use esp_weather::clock::{is_plausible, parse_http_date, TimeSource};
+
+#[test]
+fn parses_http_date() {
+    assert_eq!(
+        parse_http_date("Wed, 06 Nov 2024 08:49:37 GMT"),
+        Some(Utc.with_ymd_and_hms(2024, 11, 6, 8, 49, 37).unwrap())
+    );
+    assert_eq!(parse_http_date("yesterday"), None);
+}
+
+#[test]
+fn unset_clock_is_not_plausible() {
+    assert!(!is_plausible(Utc.timestamp_opt(0, 0).unwrap()));
+    assert!(is_plausible(Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap()));
+}
+
+#[test]
+fn only_fallbacks_are_marked() {
+    assert_eq!(TimeSource::Ntp.label(), None);
+    assert_eq!(TimeSource::Rtc.label(), Some("RTC"));
+    assert_eq!(TimeSource::Http.label(), Some("HTTP"));
+}
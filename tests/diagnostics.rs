@@ -0,0 +1,73 @@
+use std::net::Ipv4Addr;
+
+use chrono::NaiveDate;
+use embedded_graphics::{mock_display::MockDisplay, prelude::*};
+use epd_waveshare::color::TriColor;
+use esp_weather::{
+    diagnostics::{ErrorCategory, ErrorReport, FIRMWARE_VERSION},
+    locale::Language,
+    network::NetworkStatus,
+    provisioning::SETUP_URL,
+};
+
+fn report(network: Option<&NetworkStatus>) -> ErrorReport<'_> {
+    let date = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+    ErrorReport {
+        category: ErrorCategory::Service,
+        message: "Got no weather",
+        time: date.and_hms_opt(14, 5, 0).unwrap(),
+        network,
+        retry: Some(date.and_hms_opt(15, 0, 0).unwrap()),
+        access_point: "esp-weather",
+        language: Language::English,
+    }
+}
+
+#[test]
+fn lists_the_diagnostics() {
+    let network = NetworkStatus {
+        ssid: "home".to_string(),
+        signal: Some(-67),
+        ip: Some(Ipv4Addr::new(192, 168, 1, 20)),
+    };
+    assert_eq!(
+        report(Some(&network)).details(),
+        vec![
+            ("Failed", "Mar 5, 24 14:05".to_string()),
+            ("Wifi", "home (-67 dBm)".to_string()),
+            ("IP", "192.168.1.20".to_string()),
+            ("Firmware", FIRMWARE_VERSION.to_string()),
+            ("Next try", "Mar 5, 24 15:00".to_string()),
+            ("Setup", format!("esp-weather - {SETUP_URL}")),
+        ]
+    );
+}
+
+#[test]
+fn shows_missing_network() {
+    let details = report(None).details();
+    assert_eq!(details[1], ("Wifi", "not connected".to_string()));
+    assert_eq!(details[2], ("IP", "-".to_string()));
+}
+
+#[test]
+fn icons_stay_in_their_square() {
+    for category in [
+        ErrorCategory::Network,
+        ErrorCategory::Time,
+        ErrorCategory::Service,
+        ErrorCategory::Data,
+    ] {
+        let mut display = MockDisplay::<TriColor>::new();
+        display.set_allow_overdraw(true);
+        category.draw_icon(&mut display, Point::new(8, 8), 48);
+        let bounds = display.affected_area();
+        assert!(!bounds.is_zero_sized(), "{category:?} {bounds:?}");
+        assert!(
+            bounds.top_left.x >= 8 && bounds.top_left.y >= 8,
+            "{category:?} {bounds:?}"
+        );
+        let end = bounds.bottom_right().unwrap();
+        assert!(end.x < 56 && end.y < 56, "{category:?} {end:?}");
+    }
+}
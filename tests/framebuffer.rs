@@ -0,0 +1,33 @@
+use esp_weather::framebuffer::{PartialRefreshes, RefreshMode, Region};
+
+#[test]
+fn region_is_aligned_to_bytes() {
+    let region = Region::new(5, 10, 20, 4);
+    assert_eq!(region.x, 0);
+    assert_eq!(region.width, 32);
+    assert_eq!(region.y, 10);
+    assert_eq!(region.height, 4);
+}
+
+#[test]
+fn cuts_rows_of_the_region() {
+    // 32 pixels wide panel, every byte holds its index
+    let plane = (0..32u8).collect::<Vec<_>>();
+    let region = Region::new(8, 1, 16, 2);
+    assert_eq!(region.cut(&plane, 32), [5, 6, 9, 10]);
+
+    let buffer = [plane.clone(), plane.iter().map(|byte| byte + 100).collect()].concat();
+    let (black, chromatic) = region.planes(&buffer, 32);
+    assert_eq!(black, [5, 6, 9, 10]);
+    assert_eq!(chromatic, [105, 106, 109, 110]);
+}
+
+#[test]
+fn full_refresh_after_max_partials() {
+    let mut refreshes = PartialRefreshes::default();
+    assert_eq!(refreshes.next(2), RefreshMode::Partial);
+    assert_eq!(refreshes.next(2), RefreshMode::Partial);
+    assert_eq!(refreshes.next(2), RefreshMode::Full);
+    assert_eq!(refreshes.count(), 2);
+    assert_eq!(PartialRefreshes::default().next(0), RefreshMode::Full);
+}
@@ -0,0 +1,71 @@
+use esp_weather::{
+    constants::{DISPLAY_HEIGHT, DISPLAY_WIDTH},
+    framebuffer::STATUS_STRIP,
+    layout::{Area, Layout, LayoutError},
+};
+
+#[test]
+fn default_layout_fits_the_panel() {
+    let layout = Layout::default();
+    assert_eq!(layout.validate(DISPLAY_WIDTH, DISPLAY_HEIGHT), Ok(()));
+    assert_eq!(layout.status_region(), STATUS_STRIP);
+}
+
+#[test]
+fn small_panels_keep_the_header_free() {
+    let layout = Layout::for_panel(400, 300);
+    assert_eq!(layout.validate(400, 300), Ok(()));
+    let header = layout.header.rectangle();
+    for area in [layout.warnings, layout.air] {
+        assert!(header.intersection(&area.rectangle()).is_zero_sized());
+    }
+    // the badges end above the temperature labels
+    assert!(layout.air.bottom() < layout.temperature.area.y as i32);
+    assert!(layout.rain.area.bottom() <= layout.sun.y as i32);
+}
+
+#[test]
+fn overrides_single_widgets() {
+    let layout = Layout::from_json(
+        r#"{"hour_width": 8, "sun": {"x": 40, "y": 100, "width": 200, "height": 4}}"#,
+    )
+    .unwrap();
+    assert_eq!(layout.hour_width, 8);
+    assert_eq!(layout.sun, Area::new(40, 100, 200, 4));
+    assert_eq!(layout.rain, Layout::default().rain);
+    assert_eq!(layout.hours_in(&layout.sun), 25);
+    assert_eq!(layout.hour_x(&layout.sun, 2), 56);
+
+    let layout =
+        Layout::from_json(r#"{"rain": {"x": 60, "y": 100, "width": 100, "height": 40}}"#).unwrap();
+    // the options keep their defaults
+    assert_eq!(layout.rain.scale, 10.0);
+}
+
+#[test]
+fn rejects_invalid_layouts() {
+    assert_eq!(
+        Layout::from_json(&format!(
+            r#"{{"sun": {{"x": 10, "y": 0, "width": {DISPLAY_WIDTH}, "height": 3}}}}"#
+        )),
+        Err(LayoutError::OutsidePanel("sun lane"))
+    );
+    assert_eq!(
+        Layout::from_json(r#"{"header": {"x": 4294967295, "y": 0, "width": 2, "height": 60}}"#),
+        Err(LayoutError::OutsidePanel("header"))
+    );
+    assert_eq!(
+        Layout::from_json(r#"{"icons": {"x": 10, "y": 0, "width": 100, "height": 0, "every": 3}}"#),
+        Err(LayoutError::Empty("icon row"))
+    );
+    assert_eq!(
+        Layout::from_json(
+            r#"{"temperature": {"x": 60, "y": 100, "width": 100, "height": 50, "min": 20, "max": 0}}"#
+        ),
+        Err(LayoutError::TemperatureRange)
+    );
+    assert!(matches!(
+        Layout::from_json(r#"{"moon": {"x": 0, "y": 0, "width": 10, "height": 10}}"#),
+        Err(LayoutError::Parse(_))
+    ));
+}
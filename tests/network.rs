@@ -0,0 +1,100 @@
+use esp_weather::{
+    network::{connection_order, KnownNetwork, LastConnection, ScanResult},
+    secret::Secret,
+};
+
+fn network(ssid: &str, priority: u8) -> KnownNetwork {
+    KnownNetwork {
+        ssid: ssid.to_string(),
+        password: Secret::new("password"),
+        priority,
+    }
+}
+
+fn scan(ssid: &str, signal: i8, channel: u8) -> ScanResult {
+    ScanResult {
+        ssid: ssid.to_string(),
+        signal,
+        channel,
+    }
+}
+
+#[test]
+fn parses_network_list() {
+    let networks = KnownNetwork::list_from_code("office:secret:2;home:password;;guest:");
+    assert_eq!(
+        networks,
+        vec![
+            KnownNetwork {
+                ssid: "office".to_string(),
+                password: Secret::new("secret"),
+                priority: 2
+            },
+            KnownNetwork {
+                ssid: "home".to_string(),
+                password: Secret::new("password"),
+                priority: 0
+            },
+            KnownNetwork {
+                ssid: "guest".to_string(),
+                password: Secret::new(""),
+                priority: 0
+            },
+        ]
+    );
+    assert!(KnownNetwork::list_from_code("office:secret:high").is_empty());
+}
+
+#[test]
+fn strongest_network_first() {
+    let known = [network("office", 0), network("home", 0)];
+    let order = connection_order(
+        &known,
+        &[
+            scan("home", -80, 1),
+            scan("office", -50, 6),
+            scan("other", -30, 11),
+        ],
+    );
+    let ssids = order
+        .iter()
+        .map(|candidate| candidate.network.ssid.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ssids, ["office", "home"]);
+    assert_eq!(order[0].channel, Some(6));
+}
+
+#[test]
+fn priority_before_signal() {
+    let known = [network("office", 0), network("home", 1)];
+    let order = connection_order(&known, &[scan("home", -80, 1), scan("office", -50, 6)]);
+    assert_eq!(order[0].network.ssid, "home");
+}
+
+#[test]
+fn missing_networks_are_tried_last_without_channel() {
+    let known = [network("hidden", 5), network("office", 0)];
+    let order = connection_order(&known, &[scan("office", -70, 6), scan("office", -40, 11)]);
+    assert_eq!(order[0].network.ssid, "office");
+    // the strongest access point of the network is used
+    assert_eq!(order[0].channel, Some(11));
+    assert_eq!(order[1].network.ssid, "hidden");
+    assert_eq!(order[1].channel, None);
+}
+
+#[test]
+fn last_connection_finds_known_network() {
+    let known = [network("office", 0), network("home", 1)];
+    let last = LastConnection::new("office", [1, 2, 3, 4, 5, 6], 11, None).unwrap();
+    assert_eq!(last.ssid(), "office");
+
+    let candidate = last.candidate(&known).unwrap();
+    assert_eq!(candidate.network.ssid, "office");
+    assert_eq!(candidate.channel, Some(11));
+    assert_eq!(candidate.bssid, Some([1, 2, 3, 4, 5, 6]));
+
+    // the network was removed from the configuration
+    let last = LastConnection::new("guest", [1, 2, 3, 4, 5, 6], 11, None).unwrap();
+    assert!(last.candidate(&known).is_none());
+    assert!(LastConnection::new(&"x".repeat(33), [0; 6], 1, None).is_none());
+}
@@ -0,0 +1,91 @@
+use chrono::NaiveTime;
+use esp_weather::{
+    layout::Layout,
+    pages::{Page, PageSelection},
+};
+
+fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+#[test]
+fn parses_codes() {
+    assert_eq!(
+        PageSelection::from_code("week"),
+        Some(PageSelection::Fixed(Page::Week))
+    );
+    assert_eq!(
+        PageSelection::from_code("today, three-days"),
+        Some(PageSelection::Rotate(vec![Page::Today, Page::ThreeDays]))
+    );
+    assert_eq!(
+        PageSelection::from_code("06:00 today,18:30 week"),
+        Some(PageSelection::Schedule(vec![
+            (time(6, 0), Page::Today),
+            (time(18, 30), Page::Week)
+        ]))
+    );
+    assert_eq!(PageSelection::from_code("month"), None);
+    assert_eq!(PageSelection::from_code("06:00 today,week"), None);
+}
+
+#[test]
+fn fixed_page_and_button() {
+    let selection = PageSelection::Fixed(Page::Week);
+    assert_eq!(selection.select(None, false, time(12, 0)), Page::Week);
+    assert_eq!(
+        selection.select(Some(Page::ThreeDays), false, time(12, 0)),
+        Page::Week
+    );
+    // the button cycles through all pages
+    assert_eq!(
+        selection.select(Some(Page::Week), true, time(12, 0)),
+        Page::Today
+    );
+}
+
+#[test]
+fn rotates_through_the_list() {
+    let selection = PageSelection::Rotate(vec![Page::Today, Page::Week]);
+    assert_eq!(selection.select(None, false, time(12, 0)), Page::Today);
+    assert_eq!(
+        selection.select(Some(Page::Today), false, time(12, 0)),
+        Page::Week
+    );
+    assert_eq!(
+        selection.select(Some(Page::Week), false, time(12, 0)),
+        Page::Today
+    );
+    // a page which is not in the list starts it again
+    assert_eq!(
+        selection.select(Some(Page::ThreeDays), false, time(12, 0)),
+        Page::Today
+    );
+}
+
+#[test]
+fn page_of_the_time_of_day() {
+    let selection = PageSelection::Schedule(vec![
+        (time(6, 0), Page::Today),
+        (time(12, 0), Page::ThreeDays),
+        (time(20, 0), Page::Week),
+    ]);
+    assert_eq!(selection.select(None, false, time(6, 0)), Page::Today);
+    assert_eq!(selection.select(None, false, time(15, 30)), Page::ThreeDays);
+    // before the first time of the day
+    assert_eq!(selection.select(None, false, time(3, 0)), Page::Week);
+    assert_eq!(
+        selection.select(Some(Page::ThreeDays), true, time(15, 30)),
+        Page::Week
+    );
+}
+
+#[test]
+fn banner_slots() {
+    let layout = Layout::default();
+    assert_eq!(Page::ThreeDays.banner(&layout), layout.warnings);
+    for page in Page::ALL {
+        // the status strip stays visible
+        assert!(page.banner(&layout).y >= layout.status.bottom() as u32);
+    }
+}
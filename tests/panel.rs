@@ -0,0 +1,22 @@
+use embedded_graphics::prelude::*;
+use epd_waveshare::color::TriColor;
+use esp_weather::panel::MonoRed;
+
+#[test]
+fn parses_mono_red() {
+    assert_eq!(MonoRed::from_code("Black"), Some(MonoRed::Black));
+    assert_eq!(MonoRed::from_code("dither"), Some(MonoRed::Dither));
+    assert_eq!(MonoRed::from_code("red"), None);
+}
+
+#[test]
+fn red_is_dithered_or_black() {
+    let points = [Point::new(0, 0), Point::new(1, 0), Point::new(1, 1)];
+    let dithered = points.map(|point| MonoRed::Dither.is_black(TriColor::Chromatic, point));
+    assert_eq!(dithered, [true, false, true]);
+    assert!(points
+        .iter()
+        .all(|point| MonoRed::Black.is_black(TriColor::Chromatic, *point)));
+    assert!(!MonoRed::Dither.is_black(TriColor::White, Point::zero()));
+    assert!(MonoRed::Dither.is_black(TriColor::Black, Point::new(1, 0)));
+}
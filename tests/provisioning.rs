@@ -0,0 +1,121 @@
+use esp_weather::{
+    provisioning::{dns_response, form_page, generate_passphrase, ProvisioningError, Settings},
+    secret::Secret,
+    weather::Location,
+};
+
+#[test]
+fn parses_form() {
+    let settings = Settings::from_form(
+        b"ssid=Office+Wifi&password=p%40ss%26word&location=50.11%2C8.68&refresh=30",
+    )
+    .unwrap();
+    assert_eq!(
+        settings,
+        Settings {
+            ssid: "Office Wifi".to_string(),
+            password: Secret::new("p@ss&word"),
+            location: Location {
+                latitude: 50.11,
+                longitude: 8.68,
+                air_quality: false,
+            },
+            refresh: 30,
+        }
+    );
+}
+
+#[test]
+fn open_networks_have_no_password() {
+    let settings = Settings::from_form(b"ssid=guest&password=&location=1,2&refresh=60").unwrap();
+    assert!(settings.password.is_empty());
+}
+
+#[test]
+fn validates_form() {
+    let error = |body: &[u8]| Settings::from_form(body).unwrap_err();
+    assert_eq!(
+        error(b"ssid=&location=1,2&refresh=60"),
+        ProvisioningError::MissingSsid
+    );
+    assert_eq!(
+        error(b"ssid=aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa&location=1,2&refresh=60"),
+        ProvisioningError::SsidTooLong
+    );
+    assert_eq!(
+        error(b"ssid=a&password=short&location=1,2&refresh=60"),
+        ProvisioningError::InvalidPassword
+    );
+    assert_eq!(
+        error(b"ssid=a&location=frankfurt&refresh=60"),
+        ProvisioningError::InvalidLocation
+    );
+    assert_eq!(
+        error(b"ssid=a&location=95,8&refresh=60"),
+        ProvisioningError::InvalidLocation
+    );
+    assert_eq!(
+        error(b"ssid=a&location=1,2&refresh=1"),
+        ProvisioningError::InvalidRefresh
+    );
+}
+
+#[test]
+fn form_escapes_values_and_hides_password() {
+    let settings = Settings {
+        ssid: "<script>".to_string(),
+        password: Secret::new("secret-password"),
+        location: Location::default(),
+        refresh: 60,
+    };
+    let page = form_page(Some(&settings), None);
+    assert!(page.contains("&lt;script&gt;"));
+    assert!(!page.contains("<script>"));
+    assert!(!page.contains("secret-password"));
+}
+
+#[test]
+fn dns_resolves_everything_to_the_device() {
+    // query for example.com, type A, class IN with recursion desired
+    let mut query = vec![0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+    query.extend_from_slice(b"\x07example\x03com\x00");
+    query.extend_from_slice(&[0, 1, 0, 1]);
+
+    let response = dns_response(&query, [192, 168, 71, 1]).unwrap();
+    // same id, response flag, one question and one answer
+    assert_eq!(&response[0..2], &[0x12, 0x34]);
+    assert_eq!(response[2] & 0x80, 0x80);
+    assert_eq!(&response[4..8], &[0, 1, 0, 1]);
+    assert_eq!(&response[12..query.len()], &query[12..]);
+    assert_eq!(&response[response.len() - 4..], &[192, 168, 71, 1]);
+
+    // responses are not answered
+    let mut answer = query.clone();
+    answer[2] |= 0x80;
+    assert_eq!(dns_response(&answer, [192, 168, 71, 1]), None);
+    // truncated queries are ignored
+    assert_eq!(dns_response(&query[..15], [192, 168, 71, 1]), None);
+}
+
+#[test]
+fn settings_do_not_leak_the_password() {
+    let settings =
+        Settings::from_form(b"ssid=a&password=secret-password&location=1,2&refresh=60").unwrap();
+    assert_eq!(settings.password.expose(), "secret-password");
+    assert!(!format!("{settings:?}").contains("secret-password"));
+}
+
+#[test]
+fn generated_passphrase_for_wpa2() {
+    let mut seed = 7u32;
+    let passphrase = generate_passphrase(|| {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        seed >> 8
+    });
+    assert_eq!(passphrase.len(), 10);
+    // unambiguous on the display
+    assert!(passphrase
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() && !"ilo01".contains(c) && !c.is_ascii_uppercase()));
+    assert_ne!(passphrase, generate_passphrase(|| 0));
+}
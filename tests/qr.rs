@@ -0,0 +1,122 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+use epd_waveshare::color::TriColor;
+use esp_weather::qr::{QrCode, QrError, QUIET_ZONE};
+
+/// frame buffer for the rendered codes
+struct Bitmap {
+    size: Size,
+    pixels: Vec<TriColor>,
+}
+
+impl Bitmap {
+    fn new(width: u32, height: u32, color: TriColor) -> Self {
+        Self {
+            size: Size::new(width, height),
+            pixels: vec![color; (width * height) as usize],
+        }
+    }
+
+    fn is_black(&self, x: u32, y: u32) -> bool {
+        self.pixels[(y * self.size.width + x) as usize] == TriColor::Black
+    }
+}
+
+impl OriginDimensions for Bitmap {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl DrawTarget for Bitmap {
+    type Color = TriColor;
+    type Error = std::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let Ok((x, y)) = <(u32, u32)>::try_from(point) {
+                if x < self.size.width && y < self.size.height {
+                    self.pixels[(y * self.size.width + x) as usize] = color;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// render the code with a white margin and decode it again with rqrr
+fn roundtrip(text: &str, module_size: u32, quiet_zone: u32) -> String {
+    let code = QrCode::encode(text).unwrap();
+    let width = code.width(module_size, quiet_zone);
+    let mut bitmap = Bitmap::new(width + 30, width + 40, TriColor::White);
+    code.draw(&mut bitmap, Point::new(10, 20), module_size, quiet_zone);
+    let mut image = rqrr::PreparedImage::prepare_from_greyscale(
+        bitmap.size.width as usize,
+        bitmap.size.height as usize,
+        |x, y| {
+            if bitmap.is_black(x as u32, y as u32) {
+                0
+            } else {
+                255
+            }
+        },
+    );
+    let grids = image.detect_grids();
+    assert_eq!(grids.len(), 1);
+    let (_, content) = grids[0].decode().unwrap();
+    content
+}
+
+#[test]
+fn decodes_the_rendered_code() {
+    let url = "http://esp-weather.setup/";
+    assert_eq!(roundtrip(url, 3, QUIET_ZONE), url);
+    assert_eq!(roundtrip(url, 2, 0), url);
+}
+
+#[test]
+fn decodes_numeric_and_alphanumeric_text() {
+    assert_eq!(roundtrip("0123456789", 2, 2), "0123456789");
+    assert_eq!(
+        roundtrip("HTTP://EXAMPLE.COM/FORECAST", 2, 2),
+        "HTTP://EXAMPLE.COM/FORECAST"
+    );
+}
+
+#[test]
+fn decodes_codes_with_several_blocks() {
+    let text = "https://example.com/forecast?latitude=52.52&longitude=13.41&hourly=temperature_2m,precipitation";
+    let code = QrCode::encode(text).unwrap();
+    assert!(code.size() > 33, "{code:?}");
+    assert_eq!(roundtrip(text, 2, QUIET_ZONE), text);
+}
+
+#[test]
+fn clears_the_quiet_zone() {
+    let code = QrCode::encode("quiet").unwrap();
+    let width = code.width(2, QUIET_ZONE);
+    assert_eq!(width, (code.size() + 8) * 2);
+    let mut bitmap = Bitmap::new(width, width, TriColor::Chromatic);
+    code.draw(&mut bitmap, Point::zero(), 2, QUIET_ZONE);
+    let inner = Rectangle::new(Point::new_equal(8), Size::new_equal(code.size() * 2));
+    for y in 0..width {
+        for x in 0..width {
+            let color = bitmap.pixels[(y * width + x) as usize];
+            if !inner.contains(Point::new(x as i32, y as i32)) {
+                assert_eq!(color, TriColor::White, "{x} {y}");
+            }
+            assert_ne!(color, TriColor::Chromatic);
+        }
+    }
+}
+
+#[test]
+fn fits_the_module_size() {
+    let code = QrCode::encode("http://esp-weather.setup/").unwrap();
+    assert_eq!(code.size(), 25);
+    assert_eq!(code.module_size_for(100, 4), Some(3));
+    assert_eq!(code.module_size_for(32, 4), None);
+    assert_eq!(QrCode::encode(&"x".repeat(3000)), Err(QrError::TooLong));
+}
@@ -0,0 +1,135 @@
+use chrono::{NaiveTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::{America::New_York, Europe::Berlin};
+use esp_weather::schedule::RefreshPolicy;
+
+fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+#[test]
+fn parses_codes() {
+    assert_eq!(
+        RefreshPolicy::from_code("every 30m"),
+        Some(RefreshPolicy::Interval(TimeDelta::minutes(30)))
+    );
+    assert_eq!(
+        RefreshPolicy::from_code("at 06:00,18:30"),
+        Some(RefreshPolicy::Times(vec![time(6, 0), time(18, 30)]))
+    );
+    assert_eq!(
+        RefreshPolicy::from_code("every 2h quiet 22:00-06:00"),
+        Some(RefreshPolicy::QuietHours {
+            interval: TimeDelta::hours(2),
+            start: time(22, 0),
+            end: time(6, 0),
+        })
+    );
+    assert_eq!(RefreshPolicy::from_code("every 0m"), None);
+    assert_eq!(RefreshPolicy::from_code("every 9999999999999999h"), None);
+    assert_eq!(RefreshPolicy::from_code("every 9999999999999999m"), None);
+    assert_eq!(RefreshPolicy::from_code("at 25:00"), None);
+    assert_eq!(RefreshPolicy::from_code("sometimes"), None);
+}
+
+#[test]
+fn interval_is_independent_of_dst() {
+    let policy = RefreshPolicy::Interval(TimeDelta::hours(1));
+    // 01:30 local time right before the spring forward transition in berlin
+    let now = Utc.with_ymd_and_hms(2024, 3, 31, 0, 30, 0).unwrap();
+    assert_eq!(
+        policy.next_wake(now, &Berlin),
+        Utc.with_ymd_and_hms(2024, 3, 31, 1, 30, 0).unwrap()
+    );
+}
+
+#[test]
+fn times_picks_next_wall_clock_time() {
+    let policy = RefreshPolicy::Times(vec![time(6, 0), time(18, 0)]);
+    // 12:00 in berlin (summer time)
+    let now = Utc.with_ymd_and_hms(2024, 6, 1, 10, 0, 0).unwrap();
+    assert_eq!(
+        policy.next_wake(now, &Berlin),
+        Utc.with_ymd_and_hms(2024, 6, 1, 16, 0, 0).unwrap()
+    );
+    // 20:00 in berlin, the next refresh is tomorrow morning
+    let now = Utc.with_ymd_and_hms(2024, 6, 1, 18, 0, 0).unwrap();
+    assert_eq!(
+        policy.next_wake(now, &Berlin),
+        Utc.with_ymd_and_hms(2024, 6, 2, 4, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn times_use_the_forecast_timezone() {
+    let policy = RefreshPolicy::Times(vec![time(6, 0)]);
+    // 23:00 utc is 19:00 in new york
+    let now = Utc.with_ymd_and_hms(2024, 6, 1, 23, 0, 0).unwrap();
+    assert_eq!(
+        policy.next_wake(now, &New_York),
+        Utc.with_ymd_and_hms(2024, 6, 2, 10, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn times_skipped_by_spring_forward_move_after_the_gap() {
+    let policy = RefreshPolicy::Times(vec![time(2, 30)]);
+    let now = Utc.with_ymd_and_hms(2024, 3, 30, 22, 0, 0).unwrap();
+    // 02:30 does not exist on 31.03.2024 in berlin, 03:00 CEST is 01:00 utc
+    assert_eq!(
+        policy.next_wake(now, &Berlin),
+        Utc.with_ymd_and_hms(2024, 3, 31, 1, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn ambiguous_times_use_the_first_occurrence() {
+    let policy = RefreshPolicy::Times(vec![time(2, 30)]);
+    let now = Utc.with_ymd_and_hms(2024, 10, 26, 22, 0, 0).unwrap();
+    // 02:30 happens twice on 27.10.2024 in berlin, the first time is still CEST
+    assert_eq!(
+        policy.next_wake(now, &Berlin),
+        Utc.with_ymd_and_hms(2024, 10, 27, 0, 30, 0).unwrap()
+    );
+}
+
+#[test]
+fn quiet_hours_delay_until_the_end() {
+    let policy = RefreshPolicy::QuietHours {
+        interval: TimeDelta::hours(1),
+        start: time(22, 0),
+        end: time(6, 0),
+    };
+    // 20:30 in berlin, the next refresh at 21:30 is before the quiet hours
+    let now = Utc.with_ymd_and_hms(2024, 6, 1, 18, 30, 0).unwrap();
+    assert_eq!(
+        policy.next_wake(now, &Berlin),
+        Utc.with_ymd_and_hms(2024, 6, 1, 19, 30, 0).unwrap()
+    );
+    // 21:30 in berlin, 22:30 is quiet so the refresh waits for 06:00
+    let now = Utc.with_ymd_and_hms(2024, 6, 1, 19, 30, 0).unwrap();
+    assert_eq!(
+        policy.next_wake(now, &Berlin),
+        Utc.with_ymd_and_hms(2024, 6, 2, 4, 0, 0).unwrap()
+    );
+    // 01:00 in berlin after midnight is still quiet
+    let now = Utc.with_ymd_and_hms(2024, 6, 1, 23, 0, 0).unwrap();
+    assert_eq!(
+        policy.next_wake(now, &Berlin),
+        Utc.with_ymd_and_hms(2024, 6, 2, 4, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn quiet_hours_across_fall_back() {
+    let policy = RefreshPolicy::QuietHours {
+        interval: TimeDelta::hours(1),
+        start: time(1, 0),
+        end: time(4, 0),
+    };
+    // 00:30 CEST on 27.10.2024, quiet until 04:00 CET which is 03:00 utc
+    let now = Utc.with_ymd_and_hms(2024, 10, 26, 22, 30, 0).unwrap();
+    assert_eq!(
+        policy.next_wake(now, &Berlin),
+        Utc.with_ymd_and_hms(2024, 10, 27, 3, 0, 0).unwrap()
+    );
+}
@@ -0,0 +1,39 @@
+use esp_weather::text::{truncate, wrap};
+
+/// every character is 6 pixels wide
+fn measure(text: &str) -> u32 {
+    text.chars().count() as u32 * 6
+}
+
+#[test]
+fn wraps_at_words() {
+    assert_eq!(
+        wrap("could not connect to the wifi", 60, 5, measure),
+        ["could not", "connect to", "the wifi"]
+    );
+    // line breaks are kept
+    assert_eq!(wrap("no\nweather", 60, 5, measure), ["no", "weather"]);
+    assert_eq!(wrap("", 60, 5, measure), Vec::<String>::new());
+}
+
+#[test]
+fn truncates_with_an_ellipsis() {
+    assert_eq!(truncate("Donnerstag", 60, measure), "Donnerstag");
+    assert_eq!(truncate("Donnerstag", 42, measure), "Donn...");
+    // multi byte characters are not split
+    assert_eq!(truncate("Überwiegend", 36, measure), "Übe...");
+    assert_eq!(truncate("Montag", 12, measure), "");
+}
+
+#[test]
+fn ellipsis_after_the_last_line() {
+    assert_eq!(
+        wrap("could not connect to the wifi", 60, 2, measure),
+        ["could not", "connect..."]
+    );
+    // words longer than a line are shortened
+    assert_eq!(
+        wrap("Gewitter mit Hagel", 30, 3, measure),
+        ["Ge...", "mit", "Hagel"]
+    );
+}
@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use esp_weather::wake::{Press, WakeAction, WakeCause, LONG_PRESS};
+
+#[test]
+fn long_press_from_duration() {
+    assert_eq!(
+        Press::from_duration(Duration::from_millis(200)),
+        Press::Short
+    );
+    assert_eq!(Press::from_duration(LONG_PRESS), Press::Long);
+    assert_eq!(Press::from_duration(Duration::from_secs(10)), Press::Long);
+}
+
+#[test]
+fn actions_of_wake_causes() {
+    assert_eq!(WakeCause::Reset.action(), WakeAction::Scheduled);
+    assert_eq!(WakeCause::Timer.action(), WakeAction::Scheduled);
+    assert_eq!(WakeCause::Other.action(), WakeAction::Scheduled);
+    assert_eq!(
+        WakeCause::Button(Press::Short).action(),
+        WakeAction::NextPage
+    );
+    assert_eq!(
+        WakeCause::Button(Press::Long).action(),
+        WakeAction::Provision
+    );
+}
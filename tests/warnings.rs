@@ -0,0 +1,78 @@
+use chrono::{TimeZone, Utc};
+use esp_weather::{
+    layout::Area,
+    locale::Language,
+    warnings::{DwdWarnings, Severity, WarningBanner, WarningSource},
+};
+
+const WARNINGS: &[u8] = include_bytes!("fixtures/dwd_warnings.json");
+const NO_WARNINGS: &[u8] = include_bytes!("fixtures/dwd_no_warnings.json");
+
+fn frankfurt() -> DwdWarnings {
+    DwdWarnings::from_code("dwd:806412000").unwrap()
+}
+
+#[test]
+fn parses_warn_cells() {
+    assert_eq!(frankfurt().warn_cell, 806412000);
+    assert!(frankfurt().url().contains("dwd:Warnungen_Gemeinden"));
+    assert!(frankfurt().url().ends_with("WARNCELLID%3D806412000"));
+    let district = DwdWarnings::from_code("dwd:106412000").unwrap();
+    assert!(district.url().contains("dwd:Warnungen_Landkreise"));
+    assert_eq!(DwdWarnings::from_code("806412000"), None);
+    assert_eq!(DwdWarnings::from_code("dwd:frankfurt"), None);
+}
+
+#[test]
+fn keeps_the_warnings_of_the_region() {
+    let warnings = frankfurt().parse(WARNINGS).unwrap();
+    // the cancelled warning and the neighbouring city are left out, the most severe comes first
+    let events = warnings
+        .iter()
+        .map(|warning| (warning.severity, warning.event.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        vec![
+            (
+                Severity::Severe,
+                "UNWETTER VON SCHWEREM GEWITTER mit ORKANBÖEN"
+            ),
+            (Severity::Moderate, "STURMBÖEN"),
+            (Severity::Minor, "GLÄTTE"),
+        ]
+    );
+    assert_eq!(warnings[1].area, "Stadt Frankfurt am Main");
+    assert_eq!(
+        warnings[1].onset,
+        Utc.with_ymd_and_hms(2024, 3, 5, 13, 0, 0).unwrap()
+    );
+    assert_eq!(warnings[2].expires, None);
+
+    let now = Utc.with_ymd_and_hms(2024, 3, 5, 20, 0, 0).unwrap();
+    assert!(!warnings[0].is_current(now));
+    assert!(warnings[1].is_current(now));
+    assert!(warnings[2].is_current(now));
+
+    assert_eq!(frankfurt().parse(NO_WARNINGS).unwrap(), vec![]);
+    assert!(frankfurt().parse(b"warnWetter.loadWarnings({});").is_err());
+}
+
+#[test]
+fn shows_the_period_in_local_time() {
+    let warnings = frankfurt().parse(WARNINGS).unwrap();
+    let banner = WarningBanner {
+        area: Area::new(0, 112, 800, 36),
+        warnings: &warnings[1..],
+        timezone: chrono_tz::Europe::Berlin,
+        language: Language::German,
+    };
+    assert_eq!(
+        banner.period(&warnings[1]),
+        "Markante Wetterwarnung: 5. Mär 24 14:00 - 6. Mär 24 06:00 (+1)"
+    );
+    assert_eq!(
+        banner.period(&warnings[2]),
+        "Wetterwarnung: 5. Mär 24 03:00 - bis auf Weiteres (+1)"
+    );
+}
@@ -0,0 +1,57 @@
+use chrono::TimeDelta;
+use esp_weather::weather::{WeatherDaily, WeatherForecast};
+
+const FORECAST: &str = include_str!("fixtures/forecast.json");
+
+#[test]
+fn reads_uv_and_sunshine() {
+    let forecast = serde_json::from_str::<WeatherForecast>(FORECAST).unwrap();
+    let daily = &forecast.daily;
+    assert_eq!(daily.uv_index_max, vec![7.35, 4.1]);
+    assert_eq!(daily.uv_index(0), Some(7.0));
+    assert_eq!(daily.uv_index(2), None);
+    assert_eq!(
+        daily.sunshine_duration,
+        vec![
+            TimeDelta::hours(12) + TimeDelta::minutes(18),
+            TimeDelta::zero()
+        ]
+    );
+    assert_eq!(daily.sunshine_hours(0), Some(12.3));
+    assert_eq!(daily.sunshine_ratio(0), Some(0.75));
+    assert_eq!(daily.sunshine_ratio(1), Some(0.0));
+    assert_eq!(daily.sunshine_ratio(2), None);
+}
+
+#[test]
+fn sunshine_without_daylight() {
+    let daily = WeatherDaily {
+        sunshine_duration: vec![TimeDelta::zero()],
+        daylight_duration: vec![TimeDelta::zero()],
+        ..Default::default()
+    };
+    // polar night
+    assert_eq!(daily.sunshine_ratio(0), None);
+    assert_eq!(daily.sunshine_hours(0), Some(0.0));
+}
+
+#[test]
+fn negative_utc_offset() {
+    let forecast =
+        serde_json::from_str::<WeatherForecast>(include_str!("fixtures/forecast_new_york.json"))
+            .unwrap();
+    assert_eq!(forecast.utc_offset_seconds, -4 * 3600);
+    assert_eq!(forecast.timezone, "America/New_York");
+    assert_eq!(forecast.hourly.temperature_2m[0].to_string(), "72°F");
+}
+
+#[test]
+fn uv_index_rounded_like_shown() {
+    let daily = WeatherDaily {
+        uv_index_max: vec![5.6, 5.4],
+        ..Default::default()
+    };
+    // shown as "UV 6", so it is high as well
+    assert!(daily.uv_index(0).unwrap() >= WeatherDaily::UV_HIGH);
+    assert!(daily.uv_index(1).unwrap() < WeatherDaily::UV_HIGH);
+}